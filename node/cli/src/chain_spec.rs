@@ -22,9 +22,10 @@ use grandpa_primitives::AuthorityId as GrandpaId;
 use node_runtime::constants::currency::*;
 use node_runtime::Block;
 use node_runtime::{
-    wasm_binary_unwrap, AuthorityDiscoveryConfig, BabeConfig, BalancesConfig, GrandpaConfig,
+    wasm_binary_unwrap, AuthorityDiscoveryConfig, BabeConfig, BalancesConfig, CarbonAssetsConfig,
+    CouncilConfig, CouncilMembershipConfig, DatalogConfig, DemocracyConfig, GrandpaConfig,
     ImOnlineConfig, IndicesConfig, SessionConfig, SessionKeys, StakerStatus, StakingConfig,
-    SudoConfig, SystemConfig,
+    SystemConfig, TechnicalCommitteeConfig, TechnicalMembershipConfig, TreasuryConfig,
 };
 use pallet_im_online::sr25519::AuthorityId as ImOnlineId;
 use sc_chain_spec::ChainSpecExtension;
@@ -158,6 +159,12 @@ pub fn testnet_genesis(
         ]
     });
 
+    // Governance starts with the root key and validator controllers in both collectives.
+    let mut council: Vec<AccountId> = initial_authorities.iter().map(|x| x.1.clone()).collect();
+    council.push(root_key);
+    council.sort();
+    council.dedup();
+
     const ENDOWMENT: Balance = 10_000_000 * MITO;
     const STAKE: Balance = 500_000 * MITO;
 
@@ -204,10 +211,23 @@ pub fn testnet_genesis(
             slash_reward_fraction: Perbill::from_percent(10),
             ..Default::default()
         }),
-        pallet_sudo: Some(SudoConfig { key: root_key }),
+        pallet_collective_Instance1: Some(CouncilConfig::default()),
+        pallet_membership_Instance1: Some(CouncilMembershipConfig {
+            members: council.clone(),
+            phantom: Default::default(),
+        }),
+        pallet_collective_Instance2: Some(TechnicalCommitteeConfig::default()),
+        pallet_membership_Instance2: Some(TechnicalMembershipConfig {
+            members: council,
+            phantom: Default::default(),
+        }),
+        pallet_democracy: Some(DemocracyConfig::default()),
+        pallet_treasury: Some(TreasuryConfig::default()),
 
         pallet_im_online: Some(ImOnlineConfig { keys: vec![] }),
         pallet_authority_discovery: Some(AuthorityDiscoveryConfig { keys: vec![] }),
+        pallet_robonomics_datalog: Some(DatalogConfig {}),
+        pallet_carbon_assets: Some(CarbonAssetsConfig { classes: vec![] }),
     }
 }
 
@@ -126,6 +126,7 @@ pub fn new_partial(
         let shared_epoch_changes = babe_link.epoch_changes().clone();
 
         let client = client.clone();
+        let backend = backend.clone();
         let pool = transaction_pool.clone();
         let select_chain = select_chain.clone();
         let keystore = keystore.clone();
@@ -133,6 +134,7 @@ pub fn new_partial(
         let rpc_extensions_builder = move |deny_unsafe, subscription_executor| {
             let deps = node_rpc::FullDeps {
                 client: client.clone(),
+                backend: backend.clone(),
                 pool: pool.clone(),
                 select_chain: select_chain.clone(),
                 deny_unsafe,
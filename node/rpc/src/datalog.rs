@@ -0,0 +1,522 @@
+///////////////////////////////////////////////////////////////////////////////
+//
+//  Copyright 2018-2020 Airalab <research@aira.life>
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+///////////////////////////////////////////////////////////////////////////////
+//! Datalog archive and subscription RPC.
+//!
+//! Records overwritten in datalog ring buffer and merkle mountain range nodes are served
+//! from offchain indexing storage, node should be started with `--enable-offchain-indexing true`.
+
+use codec::Decode;
+use futures::{future, stream, StreamExt, TryStreamExt};
+use jsonrpc_core::futures::{future::Future as Future01, sink::Sink as Sink01};
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use jsonrpc_pubsub::{manager::SubscriptionManager, typed::Subscriber, SubscriptionId};
+use log::warn;
+use node_primitives::{AccountId, Block, BlockNumber, Hash, Moment};
+use node_runtime::DatalogRecord;
+use pallet_robonomics_datalog::{archive_key, mmr, mmr_node_key, MerkleProof, RecordChange};
+use pallet_robonomics_datalog_rpc::{DatalogEventsRuntimeApi, DatalogMmrRuntimeApi};
+use sc_client_api::BlockchainEvents;
+use sc_finality_grandpa::GrandpaJustificationStream;
+use sc_rpc::SubscriptionTaskExecutor;
+use serde::Serialize;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::offchain::{OffchainStorage, STORAGE_PREFIX};
+use sp_runtime::{generic::BlockId, traits::BlakeTwo256};
+use std::sync::Arc;
+
+/// Maximal number of records returned by one archive call.
+const MAX_ARCHIVE_RANGE: u64 = 1000;
+
+/// Datalog archive RPC methods.
+#[rpc]
+pub trait DatalogArchiveApi {
+    /// Archived records of account with sequence numbers in `[from, to)` range.
+    ///
+    /// Records missing in the archive are skipped. Archive is written on import of every
+    /// block, so a record could come from an abandoned fork; check it against
+    /// `datalog_mmrProof` when it matters.
+    #[rpc(name = "datalog_archive")]
+    fn archive(
+        &self,
+        account: AccountId,
+        from: u64,
+        to: u64,
+    ) -> Result<Vec<(u64, Moment, DatalogRecord)>>;
+
+    /// Inclusion proof of `leaf_index`-th record of account into its merkle root at best block.
+    ///
+    /// Proof is built from `O(log n)` archived nodes and checked against root kept on-chain.
+    #[rpc(name = "datalog_mmrProof")]
+    fn mmr_proof(&self, account: AccountId, leaf_index: u64) -> Result<Option<MerkleProof<Hash>>>;
+}
+
+/// A struct that implements the [`DatalogArchiveApi`].
+pub struct DatalogArchive<C, B> {
+    client: Arc<C>,
+    backend: Arc<B>,
+}
+
+impl<C, B> DatalogArchive<C, B> {
+    /// Create new `DatalogArchive` with the given reference to the client and backend.
+    pub fn new(client: Arc<C>, backend: Arc<B>) -> Self {
+        Self { client, backend }
+    }
+}
+
+impl<C, B> DatalogArchive<C, B>
+where
+    B: sc_client_api::Backend<Block>,
+{
+    fn offchain_storage(&self) -> Result<B::OffchainStorage> {
+        self.backend.offchain_storage().ok_or_else(|| RpcError {
+            code: ErrorCode::ServerError(Error::OffchainDisabled.into()),
+            message: "Offchain storage is not available.".into(),
+            data: None,
+        })
+    }
+}
+
+/// Error type of this RPC api.
+pub enum Error {
+    /// Offchain indexing storage is not available.
+    OffchainDisabled,
+    /// Requested range is too big.
+    RangeTooBig,
+    /// Archived record can't be decoded.
+    DecodeError,
+    /// The call to runtime failed.
+    RuntimeError,
+    /// Archive doesn't match the chain state.
+    ArchiveMismatch,
+}
+
+impl From<Error> for i64 {
+    fn from(e: Error) -> i64 {
+        match e {
+            Error::OffchainDisabled => 1,
+            Error::RangeTooBig => 2,
+            Error::DecodeError => 3,
+            Error::RuntimeError => 4,
+            Error::ArchiveMismatch => 5,
+        }
+    }
+}
+
+fn archive_mismatch() -> RpcError {
+    RpcError {
+        code: ErrorCode::ServerError(Error::ArchiveMismatch.into()),
+        message: "Archived nodes don't match the chain state.".into(),
+        data: None,
+    }
+}
+
+impl<C, B> DatalogArchiveApi for DatalogArchive<C, B>
+where
+    C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+    C::Api: DatalogMmrRuntimeApi<Block, AccountId, Hash>,
+    B: sc_client_api::Backend<Block> + Send + Sync + 'static,
+{
+    fn archive(
+        &self,
+        account: AccountId,
+        from: u64,
+        to: u64,
+    ) -> Result<Vec<(u64, Moment, DatalogRecord)>> {
+        if to.saturating_sub(from) > MAX_ARCHIVE_RANGE {
+            return Err(RpcError {
+                code: ErrorCode::ServerError(Error::RangeTooBig.into()),
+                message: format!("At most {} records could be requested.", MAX_ARCHIVE_RANGE),
+                data: None,
+            });
+        }
+
+        let storage = self.offchain_storage()?;
+        (from..to)
+            .filter_map(|seq| {
+                storage
+                    .get(STORAGE_PREFIX, &archive_key(&account, seq))
+                    .map(|value| (seq, value))
+            })
+            .map(|(seq, value)| {
+                <(Moment, DatalogRecord)>::decode(&mut &value[..])
+                    .map(|(moment, record)| (seq, moment, record))
+                    .map_err(|e| RpcError {
+                        code: ErrorCode::ServerError(Error::DecodeError.into()),
+                        message: "Unable to decode archived record.".into(),
+                        data: Some(format!("{:?}", e).into()),
+                    })
+            })
+            .collect()
+    }
+
+    fn mmr_proof(&self, account: AccountId, leaf_index: u64) -> Result<Option<MerkleProof<Hash>>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(self.client.info().best_hash);
+        let runtime_error = |e| RpcError {
+            code: ErrorCode::ServerError(Error::RuntimeError.into()),
+            message: "Unable to query datalog merkle mountain range.".into(),
+            data: Some(format!("{:?}", e).into()),
+        };
+
+        let leaf_count = api
+            .mmr_leaves(&at, account.clone())
+            .map_err(runtime_error)?;
+        if leaf_index >= leaf_count {
+            return Ok(None);
+        }
+
+        let storage = self.offchain_storage()?;
+        let node = |pos| {
+            storage
+                .get(STORAGE_PREFIX, &mmr_node_key(&account, pos))
+                .and_then(|value| Hash::decode(&mut &value[..]).ok())
+        };
+
+        // nodes written by blocks of abandoned forks share keys with canonical ones,
+        // so the proof is checked against the chain
+        let peaks = api.mmr_peaks(&at, account.clone()).map_err(runtime_error)?;
+        let root = mmr::root::<BlakeTwo256>(&peaks);
+        let leaf = node(mmr::leaf_pos(leaf_index)).ok_or_else(archive_mismatch)?;
+        match mmr::proof_from_nodes::<BlakeTwo256>(leaf_index, leaf_count, peaks, node) {
+            Some(proof) if mmr::verify_proof::<BlakeTwo256>(&root, &leaf, &proof) => {
+                Ok(Some(proof))
+            }
+            _ => Err(archive_mismatch()),
+        }
+    }
+}
+
+/// Datalog event pushed to subscribers.
+#[derive(Clone, Serialize)]
+#[serde(tag = "event")]
+pub enum RecordNotification {
+    /// Record added into datalog of account.
+    NewRecord {
+        /// Hash of block the record was written in.
+        block: Hash,
+        /// Datalog owner.
+        account: AccountId,
+        /// Record timestamp.
+        moment: Moment,
+        /// Record itself.
+        record: DatalogRecord,
+        /// Sequence number of record.
+        seq: u64,
+    },
+    /// Batch of records added into datalog of account.
+    BatchRecorded {
+        /// Hash of block the records were written in.
+        block: Hash,
+        /// Datalog owner.
+        account: AccountId,
+        /// Sequence number of the first record.
+        first: u64,
+        /// Number of records.
+        count: u32,
+    },
+    /// Datalog of account erased.
+    Erased {
+        /// Hash of block the datalog was erased in.
+        block: Hash,
+        /// Datalog owner.
+        account: AccountId,
+    },
+    /// Datalog of account erased by force origin.
+    ForceErased {
+        /// Hash of block the datalog was erased in.
+        block: Hash,
+        /// Datalog owner.
+        account: AccountId,
+    },
+    /// Record of account erased.
+    RecordErased {
+        /// Hash of block the record was erased in.
+        block: Hash,
+        /// Datalog owner.
+        account: AccountId,
+        /// Sequence number of record.
+        seq: u64,
+    },
+    /// Records of account older than given moment erased.
+    ErasedOlderThan {
+        /// Hash of block the records were erased in.
+        block: Hash,
+        /// Datalog owner.
+        account: AccountId,
+        /// Records older than this timestamp are erased.
+        moment: Moment,
+    },
+    /// Record of account attested by device.
+    RecordAttested {
+        /// Hash of block the record was attested in.
+        block: Hash,
+        /// Datalog owner.
+        account: AccountId,
+        /// Sequence number of record.
+        seq: u64,
+        /// Device attested the record.
+        device: AccountId,
+    },
+    /// Block left the best chain on reorg, notifications sent for it are void.
+    Retracted {
+        /// Hash of retracted block.
+        block: Hash,
+    },
+}
+
+impl RecordNotification {
+    fn new(block: Hash, change: RecordChange<AccountId, Moment, DatalogRecord>) -> Self {
+        match change {
+            RecordChange::NewRecord(account, moment, record, seq) => Self::NewRecord {
+                block,
+                account,
+                moment,
+                record,
+                seq,
+            },
+            RecordChange::BatchRecorded(account, first, count) => Self::BatchRecorded {
+                block,
+                account,
+                first,
+                count,
+            },
+            RecordChange::Erased(account) => Self::Erased { block, account },
+            RecordChange::ForceErased(account) => Self::ForceErased { block, account },
+            RecordChange::RecordErased(account, seq) => Self::RecordErased {
+                block,
+                account,
+                seq,
+            },
+            RecordChange::ErasedOlderThan(account, moment) => Self::ErasedOlderThan {
+                block,
+                account,
+                moment,
+            },
+            RecordChange::RecordAttested(account, seq, device) => Self::RecordAttested {
+                block,
+                account,
+                seq,
+                device,
+            },
+        }
+    }
+}
+
+/// Datalog subscription RPC methods.
+#[rpc]
+pub trait DatalogSubscriptionApi {
+    /// RPC metadata.
+    type Metadata;
+
+    /// Subscribe to datalog changes of given accounts, of all accounts by default.
+    ///
+    /// Changes are pushed as blocks become best, or as they are finalized by GRANDPA
+    /// when `finalized` is set. Blocks dropped from the best chain by reorg are
+    /// reported with `Retracted` notification.
+    #[pubsub(
+        subscription = "datalog_records",
+        subscribe,
+        name = "datalog_subscribeRecords"
+    )]
+    fn subscribe_records(
+        &self,
+        metadata: Self::Metadata,
+        subscriber: Subscriber<RecordNotification>,
+        accounts: Option<Vec<AccountId>>,
+        finalized: Option<bool>,
+    );
+
+    /// Unsubscribe from datalog records.
+    #[pubsub(
+        subscription = "datalog_records",
+        unsubscribe,
+        name = "datalog_unsubscribeRecords"
+    )]
+    fn unsubscribe_records(
+        &self,
+        metadata: Option<Self::Metadata>,
+        id: SubscriptionId,
+    ) -> Result<bool>;
+}
+
+/// A struct that implements the [`DatalogSubscriptionApi`].
+pub struct DatalogSubscription<C> {
+    client: Arc<C>,
+    justification_stream: GrandpaJustificationStream<Block>,
+    manager: SubscriptionManager,
+}
+
+impl<C> DatalogSubscription<C> {
+    /// Create new `DatalogSubscription` driven by GRANDPA justifications for finalized records.
+    pub fn new(
+        client: Arc<C>,
+        justification_stream: GrandpaJustificationStream<Block>,
+        executor: SubscriptionTaskExecutor,
+    ) -> Self {
+        Self {
+            client,
+            justification_stream,
+            manager: SubscriptionManager::new(Arc::new(executor)),
+        }
+    }
+}
+
+/// Block whose datalog changes are pushed to subscribers.
+enum BlockRoute {
+    /// Block joined the chain.
+    Enacted(Hash),
+    /// Block left the best chain.
+    Retracted(Hash),
+}
+
+/// Datalog changes of block for given accounts, of all accounts if `None`.
+///
+/// Changes are read through runtime api, so events are decoded by the runtime
+/// of that block. Blocks of runtimes without the api are skipped.
+fn block_changes<C>(
+    client: &C,
+    block: Hash,
+    accounts: &Option<Vec<AccountId>>,
+) -> Vec<RecordChange<AccountId, Moment, DatalogRecord>>
+where
+    C: ProvideRuntimeApi<Block>,
+    C::Api: DatalogEventsRuntimeApi<Block, AccountId, Moment, DatalogRecord>,
+{
+    match client.runtime_api().record_changes(&BlockId::hash(block)) {
+        Ok(changes) => changes
+            .into_iter()
+            .filter(|change| {
+                accounts
+                    .as_ref()
+                    .map_or(true, |accounts| accounts.contains(change.account()))
+            })
+            .collect(),
+        Err(e) => {
+            warn!("Unable to read datalog changes of block {}: {:?}", block, e);
+            vec![]
+        }
+    }
+}
+
+/// Notifications of enacted or retracted block.
+fn block_notifications<C>(
+    client: &C,
+    route: BlockRoute,
+    accounts: &Option<Vec<AccountId>>,
+) -> Vec<RecordNotification>
+where
+    C: ProvideRuntimeApi<Block>,
+    C::Api: DatalogEventsRuntimeApi<Block, AccountId, Moment, DatalogRecord>,
+{
+    match route {
+        BlockRoute::Enacted(block) => block_changes(client, block, accounts)
+            .into_iter()
+            .map(|change| RecordNotification::new(block, change))
+            .collect(),
+        // retraction is reported only for blocks with changes of interest
+        BlockRoute::Retracted(block) if !block_changes(client, block, accounts).is_empty() => {
+            vec![RecordNotification::Retracted { block }]
+        }
+        BlockRoute::Retracted(_) => vec![],
+    }
+}
+
+impl<C> DatalogSubscriptionApi for DatalogSubscription<C>
+where
+    C: HeaderBackend<Block> + BlockchainEvents<Block> + ProvideRuntimeApi<Block>,
+    C: Send + Sync + 'static,
+    C::Api: DatalogEventsRuntimeApi<Block, AccountId, Moment, DatalogRecord>,
+{
+    type Metadata = sc_rpc::Metadata;
+
+    fn subscribe_records(
+        &self,
+        _metadata: Self::Metadata,
+        subscriber: Subscriber<RecordNotification>,
+        accounts: Option<Vec<AccountId>>,
+        finalized: Option<bool>,
+    ) {
+        let client = self.client.clone();
+        let blocks = if finalized.unwrap_or(false) {
+            // justification may finalize several blocks at once
+            let mut last: BlockNumber = client.info().finalized_number;
+            self.justification_stream
+                .subscribe()
+                .map(move |_| {
+                    let finalized = client.info().finalized_number;
+                    let blocks: Vec<_> = ((last + 1)..=finalized)
+                        .filter_map(|number| client.hash(number).ok().flatten())
+                        .map(BlockRoute::Enacted)
+                        .collect();
+                    last = last.max(finalized);
+                    stream::iter(blocks)
+                })
+                .flatten()
+                .boxed()
+        } else {
+            client
+                .import_notification_stream()
+                .filter(|notification| future::ready(notification.is_new_best))
+                .map(|notification| {
+                    // on reorg blocks of the old best chain are retracted, newest first,
+                    // and blocks of the new one are enacted
+                    let mut blocks = Vec::new();
+                    if let Some(route) = notification.tree_route {
+                        blocks.extend(
+                            route
+                                .retracted()
+                                .iter()
+                                .map(|block| BlockRoute::Retracted(block.hash)),
+                        );
+                        blocks.extend(
+                            route
+                                .enacted()
+                                .iter()
+                                .map(|block| BlockRoute::Enacted(block.hash)),
+                        );
+                    }
+                    blocks.push(BlockRoute::Enacted(notification.hash));
+                    stream::iter(blocks)
+                })
+                .flatten()
+                .boxed()
+        };
+
+        let client = self.client.clone();
+        let notifications = blocks
+            .flat_map(move |route| stream::iter(block_notifications(&*client, route, &accounts)))
+            .map(|notification| Ok::<_, ()>(Ok(notification)))
+            .compat();
+
+        self.manager.add(subscriber, |sink| {
+            sink.sink_map_err(|e| warn!("Error sending notifications: {:?}", e))
+                .send_all(notifications)
+                .map(|_| ())
+        });
+    }
+
+    fn unsubscribe_records(
+        &self,
+        _metadata: Option<Self::Metadata>,
+        id: SubscriptionId,
+    ) -> Result<bool> {
+        Ok(self.manager.cancel(id))
+    }
+}
@@ -33,7 +33,7 @@
 
 use std::sync::Arc;
 
-use node_primitives::{AccountId, Balance, Block, BlockNumber, Hash, Index};
+use node_primitives::{AccountId, Balance, Block, BlockNumber, Hash, Index, Moment};
 use sc_consensus_babe::{Config, Epoch};
 use sc_consensus_babe_rpc::BabeRpcHandler;
 use sc_consensus_epochs::SharedEpochChanges;
@@ -51,6 +51,8 @@ use sp_consensus::SelectChain;
 use sp_consensus_babe::BabeApi;
 use sp_transaction_pool::TransactionPool;
 
+pub mod datalog;
+
 /// Light client extra dependencies.
 pub struct LightDeps<C, F, P> {
     /// The client instance to use.
@@ -91,6 +93,8 @@ pub struct GrandpaDeps<B> {
 pub struct FullDeps<C, P, SC, B> {
     /// The client instance to use.
     pub client: Arc<C>,
+    /// The backend instance to use.
+    pub backend: Arc<B>,
     /// Transaction pool instance.
     pub pool: Arc<P>,
     /// The SelectChain Strategy
@@ -118,17 +122,36 @@ where
     C::Api: pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance>,
     C::Api: BabeApi<Block>,
     C::Api: BlockBuilder<Block>,
+    C: sc_client_api::BlockchainEvents<Block>,
+    C::Api: pallet_robonomics_datalog_rpc::DatalogRuntimeApi<
+        Block,
+        AccountId,
+        Moment,
+        node_runtime::DatalogRecord,
+    >,
+    C::Api: pallet_robonomics_datalog_rpc::DatalogMmrRuntimeApi<Block, AccountId, Hash>,
+    C::Api: pallet_robonomics_datalog_rpc::DatalogEventsRuntimeApi<
+        Block,
+        AccountId,
+        Moment,
+        node_runtime::DatalogRecord,
+    >,
     P: TransactionPool + 'static,
     SC: SelectChain<Block> + 'static,
     B: sc_client_api::Backend<Block> + Send + Sync + 'static,
     B::State: sc_client_api::backend::StateBackend<sp_runtime::traits::HashFor<Block>>,
 {
+    use datalog::{
+        DatalogArchive, DatalogArchiveApi, DatalogSubscription, DatalogSubscriptionApi,
+    };
+    use pallet_robonomics_datalog_rpc::{Datalog, DatalogApi};
     use pallet_transaction_payment_rpc::{TransactionPayment, TransactionPaymentApi};
     use substrate_frame_rpc_system::{FullSystem, SystemApi};
 
     let mut io = jsonrpc_core::IoHandler::default();
     let FullDeps {
         client,
+        backend,
         pool,
         select_chain,
         deny_unsafe,
@@ -157,6 +180,15 @@ where
     io.extend_with(TransactionPaymentApi::to_delegate(TransactionPayment::new(
         client.clone(),
     )));
+    io.extend_with(DatalogApi::to_delegate(Datalog::new(client.clone())));
+    io.extend_with(DatalogArchiveApi::to_delegate(DatalogArchive::new(client.clone(), backend)));
+    io.extend_with(DatalogSubscriptionApi::to_delegate(
+        DatalogSubscription::new(
+            client.clone(),
+            justification_stream.clone(),
+            subscription_executor.clone(),
+        ),
+    ));
     io.extend_with(sc_consensus_babe_rpc::BabeApi::to_delegate(
         BabeRpcHandler::new(
             client,
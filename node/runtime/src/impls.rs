@@ -17,10 +17,92 @@
 ///////////////////////////////////////////////////////////////////////////////
 //! Some configurable implementations as associated type for the substrate runtime.
 
-use crate::{Authorship, Balances, NegativeImbalance};
-use frame_support::traits::{Currency, OnUnbalanced};
-use node_primitives::Balance;
+use crate::{
+    Authorship, Balances, Council, CouncilMembership, Datalog, NegativeImbalance, Origin, Runtime,
+    TechnicalMembership, Treasury,
+};
+use frame_support::{
+    sp_io::hashing::twox_128,
+    storage::unhashed,
+    traits::{
+        Contains, ContainsLengthBound, Currency, Get, Imbalance, OnRuntimeUpgrade, OnUnbalanced,
+        UnfilteredDispatchable,
+    },
+    weights::Weight,
+};
+use node_primitives::{AccountId, Balance};
+use pallet_membership::{Call as MembershipCall, Instance1, Instance2};
 use sp_runtime::traits::Convert;
+use sp_std::{prelude::*, vec};
+
+/// Sr25519 keys of devices attesting datalog records in benchmarks.
+#[cfg(feature = "runtime-benchmarks")]
+pub struct DatalogDeviceKeys;
+
+#[cfg(feature = "runtime-benchmarks")]
+const DATALOG_DEVICE_KEY: sp_runtime::KeyTypeId = sp_runtime::KeyTypeId(*b"dlog");
+
+#[cfg(feature = "runtime-benchmarks")]
+impl pallet_robonomics_datalog::DeviceKeys<AccountId, node_primitives::Signature>
+    for DatalogDeviceKeys
+{
+    fn generate() -> AccountId {
+        use sp_runtime::traits::IdentifyAccount;
+        let public = frame_support::sp_io::crypto::sr25519_generate(DATALOG_DEVICE_KEY, None);
+        sp_runtime::MultiSigner::from(public).into_account()
+    }
+
+    fn sign(device: &AccountId, payload: &[u8]) -> node_primitives::Signature {
+        let public = sp_core::sr25519::Public::from_raw(device.clone().into());
+        frame_support::sp_io::crypto::sr25519_sign(DATALOG_DEVICE_KEY, &public, payload)
+            .expect("device key is generated by the keystore; qed")
+            .into()
+    }
+}
+
+/// Datalog records retirement certificates and monitoring reports of carbon projects refer to.
+pub struct DatalogHistory;
+impl pallet_carbon_assets::RecordHistory<AccountId> for DatalogHistory {
+    fn contains(account: &AccountId, seq: u64) -> bool {
+        seq < Datalog::datalogidx(account).total()
+    }
+
+    #[cfg(feature = "runtime-benchmarks")]
+    fn note_record(account: &AccountId, seq: u64) {
+        Balances::make_free_balance_be(account, Balance::max_value() / 2);
+        while !Self::contains(account, seq) {
+            let call = pallet_robonomics_datalog::Call::<Runtime>::record(vec![0x42].into());
+            let _ = call.dispatch_bypass_filter(Origin::signed(account.clone()));
+        }
+    }
+}
+
+/// Hands privileges of the removed sudo module over to on-chain governance.
+///
+/// Former sudo key becomes the only member of the council and the technical committee
+/// unless they have members already, then the key is removed from storage.
+pub struct RetireSudo;
+impl OnRuntimeUpgrade for RetireSudo {
+    fn on_runtime_upgrade() -> Weight {
+        let key = [twox_128(b"Sudo"), twox_128(b"Key")].concat();
+        let sudo = match unhashed::get::<AccountId>(&key) {
+            Some(sudo) => sudo,
+            None => return 0,
+        };
+
+        if CouncilMembership::members().is_empty() {
+            let call = MembershipCall::<Runtime, Instance1>::reset_members(vec![sudo.clone()]);
+            let _ = call.dispatch_bypass_filter(Origin::root());
+        }
+        if TechnicalMembership::members().is_empty() {
+            let call = MembershipCall::<Runtime, Instance2>::reset_members(vec![sudo]);
+            let _ = call.dispatch_bypass_filter(Origin::root());
+        }
+        unhashed::kill(&key);
+
+        <Runtime as frame_system::Trait>::DbWeight::get().reads_writes(3, 7)
+    }
+}
 
 pub struct Author;
 impl OnUnbalanced<NegativeImbalance> for Author {
@@ -29,6 +111,50 @@ impl OnUnbalanced<NegativeImbalance> for Author {
     }
 }
 
+/// Splits transaction fees and tips: 80% go to the treasury, 20% to the block author.
+pub struct DealWithFees;
+impl OnUnbalanced<NegativeImbalance> for DealWithFees {
+    fn on_unbalanceds<B>(mut fees_then_tips: impl Iterator<Item = NegativeImbalance>) {
+        if let Some(fees) = fees_then_tips.next() {
+            let mut split = fees.ration(80, 20);
+            if let Some(tips) = fees_then_tips.next() {
+                tips.ration_merge_into(80, 20, &mut split);
+            }
+            Treasury::on_unbalanced(split.0);
+            Author::on_unbalanced(split.1);
+        }
+    }
+}
+
+/// Council members are allowed to tip treasury.
+pub struct CouncilTippers;
+impl Contains<AccountId> for CouncilTippers {
+    fn sorted_members() -> Vec<AccountId> {
+        Council::members()
+    }
+
+    #[cfg(feature = "runtime-benchmarks")]
+    fn add(who: &AccountId) {
+        use frame_support::traits::ChangeMembers;
+        let old = Council::members();
+        let mut new = old.clone();
+        if let Err(pos) = new.binary_search(who) {
+            new.insert(pos, who.clone());
+            <Council as ChangeMembers<AccountId>>::set_members_sorted(&new, &old);
+        }
+    }
+}
+
+impl ContainsLengthBound for CouncilTippers {
+    fn min_len() -> usize {
+        0
+    }
+
+    fn max_len() -> usize {
+        crate::CouncilMaxMembers::get() as usize
+    }
+}
+
 /// Struct that handles the conversion of Balance -> `u64`. This is used for staking's election
 /// calculation.
 pub struct CurrencyToVoteHandler;
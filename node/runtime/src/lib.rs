@@ -23,15 +23,16 @@
 #![allow(clippy::identity_op)]
 use sp_std::prelude::*;
 
-use codec::Encode;
+use codec::{Decode, Encode};
 use frame_support::{
     construct_runtime, debug, parameter_types,
-    traits::{Currency, KeyOwnerProofSystem, Randomness},
+    traits::{Currency, InstanceFilter, KeyOwnerProofSystem, Randomness},
     weights::{
         constants::{BlockExecutionWeight, ExtrinsicBaseWeight, RocksDbWeight, WEIGHT_PER_SECOND},
         Weight, WeightToFeeCoefficient, WeightToFeeCoefficients, WeightToFeePolynomial,
     },
 };
+use frame_system::{EnsureOneOf, EnsureRoot};
 pub use node_primitives::{AccountId, Signature};
 use node_primitives::{AccountIndex, Balance, BlockNumber, Hash, Index, Moment};
 use pallet_grandpa::fg_primitives;
@@ -42,7 +43,11 @@ pub use pallet_transaction_payment::{Multiplier, TargetedFeeAdjustment};
 use pallet_transaction_payment_rpc_runtime_api::RuntimeDispatchInfo;
 use sp_api::impl_runtime_apis;
 use sp_authority_discovery::AuthorityId as AuthorityDiscoveryId;
-use sp_core::{crypto::KeyTypeId, OpaqueMetadata};
+use sp_core::{
+    crypto::KeyTypeId,
+    u32_trait::{_1, _2, _3, _4, _5},
+    OpaqueMetadata,
+};
 use sp_inherents::{CheckInherentsResult, InherentData};
 use sp_runtime::curve::PiecewiseLinear;
 use sp_runtime::traits::{
@@ -53,8 +58,8 @@ use sp_runtime::transaction_validity::{
     TransactionPriority, TransactionSource, TransactionValidity,
 };
 use sp_runtime::{
-    create_runtime_str, generic, impl_opaque_keys, ApplyExtrinsicResult, FixedPointNumber, Perbill,
-    Perquintill,
+    create_runtime_str, generic, impl_opaque_keys, ApplyExtrinsicResult, FixedPointNumber, ModuleId,
+    Perbill, Percent, Permill, Perquintill, RuntimeDebug,
 };
 #[cfg(any(feature = "std", test))]
 use sp_version::NativeVersion;
@@ -72,7 +77,7 @@ pub use sp_runtime::BuildStorage;
 
 /// Implementations of some helper traits passed into runtime modules as associated types.
 pub mod impls;
-use impls::CurrencyToVoteHandler;
+use impls::{CouncilTippers, CurrencyToVoteHandler, DealWithFees, RetireSudo};
 
 /// Constant values used within the runtime.
 pub mod constants;
@@ -102,10 +107,10 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
     // and set impl_version to 0. If only runtime
     // implementation changes and behavior does not, then leave spec_version as
     // is and increment impl_version.
-    spec_version: 20,
+    spec_version: 47,
     impl_version: 20,
     apis: RUNTIME_API_VERSIONS,
-    transaction_version: 2,
+    transaction_version: 3,
 };
 
 /// Native version.
@@ -189,6 +194,102 @@ impl pallet_multisig::Trait for Runtime {
     type WeightInfo = ();
 }
 
+parameter_types! {
+    // One storage item; key size 32, value size 8.
+    pub const ProxyDepositBase: Balance = deposit(1, 8);
+    // Additional storage item size of 33 bytes.
+    pub const ProxyDepositFactor: Balance = deposit(0, 33);
+    pub const MaxProxies: u16 = 32;
+    pub const AnnouncementDepositBase: Balance = deposit(1, 8);
+    pub const AnnouncementDepositFactor: Balance = deposit(0, 66);
+    pub const MaxPending: u16 = 32;
+}
+
+/// The type used to represent the kinds of proxying allowed.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Encode, Decode, RuntimeDebug)]
+pub enum ProxyType {
+    /// All calls are allowed.
+    Any,
+    /// Any calls except ones moving funds and carbon units.
+    NonTransfer,
+    /// Staking and session keys management.
+    Staking,
+    /// Council, democracy and treasury calls.
+    Governance,
+    /// Writing into datalog.
+    Datalog,
+    /// Carbon asset and project registry management.
+    AssetRegistry,
+}
+
+impl Default for ProxyType {
+    fn default() -> Self {
+        Self::Any
+    }
+}
+
+impl InstanceFilter<Call> for ProxyType {
+    fn filter(&self, c: &Call) -> bool {
+        match self {
+            ProxyType::Any => true,
+            ProxyType::NonTransfer => !matches!(
+                c,
+                Call::Balances(..)
+                    | Call::Indices(pallet_indices::Call::transfer(..))
+                    | Call::CarbonAssets(pallet_carbon_assets::Call::transfer(..))
+                    | Call::CarbonAssets(pallet_carbon_assets::Call::retire(..))
+            ),
+            ProxyType::Staking => matches!(
+                c,
+                Call::Staking(..) | Call::Session(..) | Call::Utility(..)
+            ),
+            ProxyType::Governance => matches!(
+                c,
+                Call::Democracy(..)
+                    | Call::Council(..)
+                    | Call::CouncilMembership(..)
+                    | Call::TechnicalCommittee(..)
+                    | Call::TechnicalMembership(..)
+                    | Call::Treasury(..)
+                    | Call::Scheduler(..)
+                    | Call::Utility(..)
+            ),
+            ProxyType::Datalog => matches!(c, Call::Datalog(..) | Call::Utility(..)),
+            ProxyType::AssetRegistry => matches!(
+                c,
+                Call::CarbonAssets(..) | Call::CarbonProjects(..) | Call::Utility(..)
+            ),
+        }
+    }
+
+    fn is_superset(&self, o: &Self) -> bool {
+        match (self, o) {
+            (x, y) if x == y => true,
+            (ProxyType::Any, _) => true,
+            (_, ProxyType::Any) => false,
+            (ProxyType::NonTransfer, ProxyType::Staking)
+            | (ProxyType::NonTransfer, ProxyType::Governance)
+            | (ProxyType::NonTransfer, ProxyType::Datalog) => true,
+            _ => false,
+        }
+    }
+}
+
+impl pallet_proxy::Trait for Runtime {
+    type Event = Event;
+    type Call = Call;
+    type Currency = Balances;
+    type ProxyType = ProxyType;
+    type ProxyDepositBase = ProxyDepositBase;
+    type ProxyDepositFactor = ProxyDepositFactor;
+    type MaxProxies = MaxProxies;
+    type WeightInfo = ();
+    type MaxPending = MaxPending;
+    type CallHasher = BlakeTwo256;
+    type AnnouncementDepositBase = AnnouncementDepositBase;
+    type AnnouncementDepositFactor = AnnouncementDepositFactor;
+}
+
 parameter_types! {
     pub const EpochDuration: u64 = EPOCH_DURATION_IN_SLOTS;
     pub const ExpectedBlockTime: Moment = MILLISECS_PER_BLOCK;
@@ -270,7 +371,7 @@ impl WeightToFeePolynomial for WeightToFee {
 
 impl pallet_transaction_payment::Trait for Runtime {
     type Currency = Balances;
-    type OnTransactionPayment = (); //DealWithFees;
+    type OnTransactionPayment = DealWithFees;
     type TransactionByteFee = TransactionByteFee;
     type WeightToFee = WeightToFee; //IdentityFee<Balance>;
     type FeeMultiplierUpdate =
@@ -357,15 +458,19 @@ impl pallet_staking::Trait for Runtime {
     type Currency = Balances;
     type UnixTime = Timestamp;
     type CurrencyToVote = CurrencyToVoteHandler;
-    type RewardRemainder = (); //Treasury;
+    type RewardRemainder = Treasury;
     type Event = Event;
-    type Slash = (); //Treasury; send the slashed funds to the treasury.
+    type Slash = Treasury; // send the slashed funds to the treasury.
     type Reward = (); // rewards are minted from the void
     type SessionsPerEra = SessionsPerEra;
     type BondingDuration = BondingDuration;
     type SlashDeferDuration = SlashDeferDuration;
     /// A super-majority of the council can cancel the slash.
-    type SlashCancelOrigin = frame_system::EnsureRoot<<Self as frame_system::Trait>::AccountId>;
+    type SlashCancelOrigin = EnsureOneOf<
+        AccountId,
+        EnsureRoot<AccountId>,
+        pallet_collective::EnsureProportionAtLeast<_3, _4, AccountId, CouncilCollective>,
+    >;
     type SessionInterface = Self;
     type RewardCurve = RewardCurve;
     type NextNewSession = Session;
@@ -396,15 +501,203 @@ impl pallet_identity::Trait for Runtime {
     type MaxSubAccounts = MaxSubAccounts;
     type MaxAdditionalFields = MaxAdditionalFields;
     type MaxRegistrars = MaxRegistrars;
-    type Slashed = ();
-    type ForceOrigin = frame_system::EnsureRoot<<Self as frame_system::Trait>::AccountId>;
-    type RegistrarOrigin = frame_system::EnsureRoot<<Self as frame_system::Trait>::AccountId>;
+    type Slashed = Treasury;
+    type ForceOrigin = EnsureRootOrHalfCouncil;
+    type RegistrarOrigin = EnsureRootOrHalfCouncil;
+    type WeightInfo = ();
+}
+
+parameter_types! {
+    pub const CouncilMotionDuration: BlockNumber = 5 * DAYS;
+    pub const CouncilMaxProposals: u32 = 100;
+    pub const CouncilMaxMembers: u32 = 100;
+}
+
+type CouncilCollective = pallet_collective::Instance1;
+impl pallet_collective::Trait<CouncilCollective> for Runtime {
+    type Origin = Origin;
+    type Proposal = Call;
+    type Event = Event;
+    type MotionDuration = CouncilMotionDuration;
+    type MaxProposals = CouncilMaxProposals;
+    type MaxMembers = CouncilMaxMembers;
+    type WeightInfo = ();
+}
+
+/// Root or more than half of the council.
+pub type EnsureRootOrHalfCouncil = EnsureOneOf<
+    AccountId,
+    EnsureRoot<AccountId>,
+    pallet_collective::EnsureProportionMoreThan<_1, _2, AccountId, CouncilCollective>,
+>;
+
+impl pallet_membership::Trait<pallet_membership::Instance1> for Runtime {
+    type Event = Event;
+    type AddOrigin = EnsureRootOrHalfCouncil;
+    type RemoveOrigin = EnsureRootOrHalfCouncil;
+    type SwapOrigin = EnsureRootOrHalfCouncil;
+    type ResetOrigin = EnsureRootOrHalfCouncil;
+    type PrimeOrigin = EnsureRootOrHalfCouncil;
+    type MembershipInitialized = Council;
+    type MembershipChanged = Council;
+}
+
+parameter_types! {
+    pub const TechnicalMotionDuration: BlockNumber = 5 * DAYS;
+    pub const TechnicalMaxProposals: u32 = 100;
+    pub const TechnicalMaxMembers: u32 = 100;
+}
+
+type TechnicalCollective = pallet_collective::Instance2;
+impl pallet_collective::Trait<TechnicalCollective> for Runtime {
+    type Origin = Origin;
+    type Proposal = Call;
+    type Event = Event;
+    type MotionDuration = TechnicalMotionDuration;
+    type MaxProposals = TechnicalMaxProposals;
+    type MaxMembers = TechnicalMaxMembers;
     type WeightInfo = ();
 }
 
-impl pallet_sudo::Trait for Runtime {
+impl pallet_membership::Trait<pallet_membership::Instance2> for Runtime {
     type Event = Event;
+    type AddOrigin = EnsureRootOrHalfCouncil;
+    type RemoveOrigin = EnsureRootOrHalfCouncil;
+    type SwapOrigin = EnsureRootOrHalfCouncil;
+    type ResetOrigin = EnsureRootOrHalfCouncil;
+    type PrimeOrigin = EnsureRootOrHalfCouncil;
+    type MembershipInitialized = TechnicalCommittee;
+    type MembershipChanged = TechnicalCommittee;
+}
+
+parameter_types! {
+    pub const LaunchPeriod: BlockNumber = 7 * DAYS;
+    pub const VotingPeriod: BlockNumber = 7 * DAYS;
+    pub const FastTrackVotingPeriod: BlockNumber = 3 * HOURS;
+    pub const InstantAllowed: bool = true;
+    pub const MinimumDeposit: Balance = 100 * MITO;
+    pub const EnactmentPeriod: BlockNumber = 8 * DAYS;
+    pub const CooloffPeriod: BlockNumber = 7 * DAYS;
+    pub const PreimageByteDeposit: Balance = deposit(0, 1);
+    pub const MaxVotes: u32 = 100;
+    pub const MaxProposals: u32 = 100;
+}
+
+impl pallet_democracy::Trait for Runtime {
+    type Proposal = Call;
+    type Event = Event;
+    type Currency = Balances;
+    type EnactmentPeriod = EnactmentPeriod;
+    type LaunchPeriod = LaunchPeriod;
+    type VotingPeriod = VotingPeriod;
+    type MinimumDeposit = MinimumDeposit;
+    /// A straight majority of the council can decide what their next motion is.
+    type ExternalOrigin =
+        pallet_collective::EnsureProportionAtLeast<_1, _2, AccountId, CouncilCollective>;
+    /// A super-majority can have the next scheduled referendum be a straight majority-carries vote.
+    type ExternalMajorityOrigin =
+        pallet_collective::EnsureProportionAtLeast<_3, _4, AccountId, CouncilCollective>;
+    /// A unanimous council can have the next scheduled referendum be a straight default-carries
+    /// (NTB) vote.
+    type ExternalDefaultOrigin =
+        pallet_collective::EnsureProportionAtLeast<_1, _1, AccountId, CouncilCollective>;
+    /// Two thirds of the technical committee can have an ExternalMajority/ExternalDefault vote
+    /// be tabled immediately and with a shorter voting/enactment period.
+    type FastTrackOrigin =
+        pallet_collective::EnsureProportionAtLeast<_2, _3, AccountId, TechnicalCollective>;
+    type InstantOrigin =
+        pallet_collective::EnsureProportionAtLeast<_1, _1, AccountId, TechnicalCollective>;
+    type InstantAllowed = InstantAllowed;
+    type FastTrackVotingPeriod = FastTrackVotingPeriod;
+    /// To cancel a proposal which has been passed, 2/3 of the council must agree to it.
+    type CancellationOrigin =
+        pallet_collective::EnsureProportionAtLeast<_2, _3, AccountId, CouncilCollective>;
+    /// To cancel a proposal before it has been passed, the technical committee must be unanimous
+    /// or root must agree.
+    type CancelProposalOrigin = EnsureOneOf<
+        AccountId,
+        EnsureRoot<AccountId>,
+        pallet_collective::EnsureProportionAtLeast<_1, _1, AccountId, TechnicalCollective>,
+    >;
+    type BlacklistOrigin = EnsureRoot<AccountId>;
+    /// Any single technical committee member may veto a coming council proposal, however they
+    /// can only do it once and it lasts only for the cooloff period.
+    type VetoOrigin = pallet_collective::EnsureMember<AccountId, TechnicalCollective>;
+    type CooloffPeriod = CooloffPeriod;
+    type PreimageByteDeposit = PreimageByteDeposit;
+    type OperationalPreimageOrigin = pallet_collective::EnsureMember<AccountId, CouncilCollective>;
+    type Slash = Treasury;
+    type Scheduler = Scheduler;
+    type PalletsOrigin = OriginCaller;
+    type MaxVotes = MaxVotes;
+    type MaxProposals = MaxProposals;
+    type WeightInfo = ();
+}
+
+parameter_types! {
+    pub const ProposalBond: Permill = Permill::from_percent(5);
+    pub const ProposalBondMinimum: Balance = 10 * MITO;
+    pub const SpendPeriod: BlockNumber = 1 * DAYS;
+    pub const Burn: Permill = Permill::from_percent(1);
+    pub const TipCountdown: BlockNumber = 1 * DAYS;
+    pub const TipFindersFee: Percent = Percent::from_percent(20);
+    pub const TipReportDepositBase: Balance = 1 * MITO;
+    pub const DataDepositPerByte: Balance = deposit(0, 1);
+    pub const BountyDepositBase: Balance = 1 * MITO;
+    pub const BountyDepositPayoutDelay: BlockNumber = 1 * DAYS;
+    pub const TreasuryModuleId: ModuleId = ModuleId(*b"py/trsry");
+    pub const BountyUpdatePeriod: BlockNumber = 14 * DAYS;
+    pub const MaximumReasonLength: u32 = 16384;
+    pub const BountyCuratorDeposit: Permill = Permill::from_percent(50);
+    pub const BountyValueMinimum: Balance = 10 * MITO;
+}
+
+impl pallet_treasury::Trait for Runtime {
+    type ModuleId = TreasuryModuleId;
+    type Currency = Balances;
+    type ApproveOrigin = EnsureOneOf<
+        AccountId,
+        EnsureRoot<AccountId>,
+        pallet_collective::EnsureProportionAtLeast<_3, _5, AccountId, CouncilCollective>,
+    >;
+    type RejectOrigin = EnsureRootOrHalfCouncil;
+    type Tippers = CouncilTippers;
+    type TipCountdown = TipCountdown;
+    type TipFindersFee = TipFindersFee;
+    type TipReportDepositBase = TipReportDepositBase;
+    type DataDepositPerByte = DataDepositPerByte;
+    type Event = Event;
+    type OnSlash = Treasury;
+    type ProposalBond = ProposalBond;
+    type ProposalBondMinimum = ProposalBondMinimum;
+    type SpendPeriod = SpendPeriod;
+    type Burn = Burn;
+    type BountyDepositBase = BountyDepositBase;
+    type BountyDepositPayoutDelay = BountyDepositPayoutDelay;
+    type BountyUpdatePeriod = BountyUpdatePeriod;
+    type BountyCuratorDeposit = BountyCuratorDeposit;
+    type BountyValueMinimum = BountyValueMinimum;
+    type MaximumReasonLength = MaximumReasonLength;
+    type BurnDestination = ();
+    type WeightInfo = ();
+}
+
+parameter_types! {
+    /// Scheduled calls may use up to 80% of block weight, the rest is left for extrinsics.
+    pub MaximumSchedulerWeight: Weight = Perbill::from_percent(80) * MaximumBlockWeight::get();
+    pub const MaxScheduledPerBlock: u32 = 50;
+}
+
+impl pallet_scheduler::Trait for Runtime {
+    type Event = Event;
+    type Origin = Origin;
+    type PalletsOrigin = OriginCaller;
     type Call = Call;
+    type MaximumWeight = MaximumSchedulerWeight;
+    /// Root or council majority schedule calls and cancel them, including named tasks.
+    type ScheduleOrigin = EnsureRootOrHalfCouncil;
+    type MaxScheduledPerBlock = MaxScheduledPerBlock;
+    type WeightInfo = ();
 }
 
 parameter_types! {
@@ -528,15 +821,105 @@ impl pallet_finality_tracker::Trait for Runtime {
 
 parameter_types! {
     pub const DatalogWindowSize: u64 = 128;
+    // `set_window_size` moves every record of the window, its weight at
+    // the maximum window should fit into a single extrinsic.
+    pub const DatalogMaximumWindowSize: u64 = 2048;
     pub const DatalogMaximumMessageSize: usize = 512;
+    // One storage item; key size is 32 + 8 + 16; moment and sequence number
+    // are up to 9 bytes each, block number is 5 bytes.
+    pub const DatalogDepositPerItem: Balance = deposit(1, 79);
+    pub const DatalogDepositPerByte: Balance = deposit(0, 1);
+    // Writer permission and its deposit, keys are 32 + 2 * (8 + 32) bytes each;
+    // permission is up to 15 bytes, deposit is 16 bytes.
+    pub const DatalogWriterDeposit: Balance = deposit(2, 255);
+    // Allowed sender flag and its deposit, keys are the same as writer ones.
+    pub const DatalogSenderDeposit: Balance = deposit(2, 241);
+    // Retention period and its deposit, keys are 32 + 8 + 32 bytes each.
+    pub const DatalogRetentionDeposit: Balance = deposit(2, 169);
+    // Device with its owner and the latest timestamp, and its deposit;
+    // keys are 32 + 8 + 32 bytes each.
+    pub const DatalogDeviceDeposit: Balance = deposit(2, 201);
+    pub const DatalogMaxPrunePerRecord: u32 = 8;
+    pub const DatalogMaxBatchLength: u32 = 32;
+    pub const DatalogBatchRecordEvents: bool = true;
+    // Every migrated account writes up to `DatalogWindowSize` records with their history.
+    pub const DatalogMaxMigratedPerBlock: u32 = 8;
 }
 
+/// Datalog record type.
+pub type DatalogRecord = pallet_robonomics_datalog::TypedRecord;
+
 impl pallet_robonomics_datalog::Trait for Runtime {
     type Time = Timestamp;
-    type Record = Vec<u8>;
+    type Record = DatalogRecord;
     type Event = Event;
     type WindowSize = DatalogWindowSize;
+    type MaximumWindowSize = DatalogMaximumWindowSize;
     type MaximumMessageSize = DatalogMaximumMessageSize;
+    type Currency = Balances;
+    type DepositPerItem = DatalogDepositPerItem;
+    type DepositPerByte = DatalogDepositPerByte;
+    type WriterDeposit = DatalogWriterDeposit;
+    type SenderDeposit = DatalogSenderDeposit;
+    type RetentionDeposit = DatalogRetentionDeposit;
+    type DeviceDeposit = DatalogDeviceDeposit;
+    type MaxPrunePerRecord = DatalogMaxPrunePerRecord;
+    type MaxBatchLength = DatalogMaxBatchLength;
+    type BatchRecordEvents = DatalogBatchRecordEvents;
+    type MaxMigratedPerBlock = DatalogMaxMigratedPerBlock;
+    type ForceOrigin = EnsureRootOrHalfCouncil;
+    type Signature = Signature;
+    type Public = <Signature as traits::Verify>::Signer;
+    #[cfg(feature = "runtime-benchmarks")]
+    type DeviceKeys = impls::DatalogDeviceKeys;
+    type WeightInfo = ();
+}
+
+parameter_types! {
+    pub const CarbonAssetsMaxProjectLength: u32 = 64;
+    pub const CarbonAssetsMaxPurposeLength: u32 = 256;
+    // Certificates are permanent, so their storage is paid by burned fee:
+    // certificate with its beneficiary index entry, purpose is charged per byte.
+    pub const CarbonAssetsCertificateFeeBase: Balance = deposit(2, 128);
+    pub const CarbonAssetsCertificateFeePerByte: Balance = deposit(0, 1);
+}
+
+/// Identifier of carbon asset class.
+pub type CarbonAssetId = u32;
+
+impl pallet_carbon_assets::Trait for Runtime {
+    type Event = Event;
+    type AssetId = CarbonAssetId;
+    type Balance = Balance;
+    type RegistrarOrigin = EnsureRootOrHalfCouncil;
+    type MaxProjectLength = CarbonAssetsMaxProjectLength;
+    type MaxPurposeLength = CarbonAssetsMaxPurposeLength;
+    type RecordHistory = impls::DatalogHistory;
+    type Currency = Balances;
+    type CertificateFeeBase = CarbonAssetsCertificateFeeBase;
+    type CertificateFeePerByte = CarbonAssetsCertificateFeePerByte;
+    type WeightInfo = ();
+}
+
+parameter_types! {
+    pub const CarbonProjectsMaxReportRecords: u32 = 64;
+    /// Minimal balance of asset classes created for project vintages.
+    pub const CarbonProjectsClassMinBalance: Balance = 1_000;
+    // Project and report entries with their deposit entries,
+    // report records are charged per account and sequence number.
+    pub const CarbonProjectsProjectDeposit: Balance = deposit(2, 128);
+    pub const CarbonProjectsReportDepositBase: Balance = deposit(2, 128);
+    pub const CarbonProjectsReportDepositPerRecord: Balance = deposit(0, 40);
+}
+
+impl pallet_carbon_projects::Trait for Runtime {
+    type Event = Event;
+    type MaxReportRecords = CarbonProjectsMaxReportRecords;
+    type ClassMinBalance = CarbonProjectsClassMinBalance;
+    type Currency = Balances;
+    type ProjectDeposit = CarbonProjectsProjectDeposit;
+    type ReportDepositBase = CarbonProjectsReportDepositBase;
+    type ReportDepositPerRecord = CarbonProjectsReportDepositPerRecord;
     type WeightInfo = ();
 }
 
@@ -570,10 +953,25 @@ construct_runtime!(
         AuthorityDiscovery: pallet_authority_discovery::{Module, Call, Config},
 
         // Robonomics Network modules.
-        Datalog: pallet_robonomics_datalog::{Module, Call, Storage, Event<T>},
+        Datalog: pallet_robonomics_datalog::{Module, Call, Storage, Config, Event<T>},
+
+        // Modules below are appended to keep indices of modules above.
+
+        // IPCI ecological assets.
+        CarbonAssets: pallet_carbon_assets::{Module, Call, Storage, Config<T>, Event<T>},
+        CarbonProjects: pallet_carbon_projects::{Module, Call, Storage, Event<T>},
+
+        // Governance.
+        Council: pallet_collective::<Instance1>::{Module, Call, Storage, Origin<T>, Event<T>, Config<T>},
+        CouncilMembership: pallet_membership::<Instance1>::{Module, Call, Storage, Event<T>, Config<T>},
+        TechnicalCommittee: pallet_collective::<Instance2>::{Module, Call, Storage, Origin<T>, Event<T>, Config<T>},
+        TechnicalMembership: pallet_membership::<Instance2>::{Module, Call, Storage, Event<T>, Config<T>},
+        Democracy: pallet_democracy::{Module, Call, Storage, Config, Event<T>},
+        Scheduler: pallet_scheduler::{Module, Call, Storage, Event<T>},
+        Treasury: pallet_treasury::{Module, Call, Storage, Config, Event<T>},
 
-        // Sudo. Usable initially.
-        Sudo: pallet_sudo::{Module, Call, Storage, Event<T>, Config<T>},
+        // Proxy accounts.
+        Proxy: pallet_proxy::{Module, Call, Storage, Event<T>},
     }
 );
 
@@ -610,6 +1008,7 @@ pub type Executive = frame_executive::Executive<
     frame_system::ChainContext<Runtime>,
     Runtime,
     AllModules,
+    RetireSudo,
 >;
 
 impl_runtime_apis! {
@@ -768,6 +1167,132 @@ impl_runtime_apis! {
         }
     }
 
+    impl pallet_robonomics_datalog_rpc_runtime_api::DatalogApi<
+        Block,
+        AccountId,
+        Moment,
+        DatalogRecord,
+    > for Runtime {
+        fn records(account: AccountId) -> Vec<(Moment, DatalogRecord)> {
+            Datalog::data(&account).into_iter().map(|item| item.into()).collect()
+        }
+
+        fn records_range(
+            account: AccountId,
+            from: Moment,
+            to: Moment,
+        ) -> Vec<(Moment, DatalogRecord)> {
+            Datalog::data_range(&account, from, to).into_iter().map(|item| item.into()).collect()
+        }
+
+        fn records_last(account: AccountId, count: u64) -> Vec<(Moment, DatalogRecord)> {
+            Datalog::data_last(&account, count).into_iter().map(|item| item.into()).collect()
+        }
+
+        fn records_page(
+            account: AccountId,
+            cursor: u64,
+            limit: u64,
+        ) -> (Vec<(Moment, DatalogRecord)>, Option<u64>) {
+            let (page, next) = Datalog::data_page(&account, cursor, limit);
+            (page.into_iter().map(|item| item.into()).collect(), next)
+        }
+
+        fn index(account: AccountId) -> pallet_robonomics_datalog::RingBufferIndex {
+            Datalog::datalogidx(&account)
+        }
+
+        fn window_size(account: AccountId) -> u64 {
+            Datalog::window_size(&account)
+        }
+
+        fn inbox(account: AccountId) -> Vec<(AccountId, Moment, DatalogRecord)> {
+            Datalog::inbox(&account).into_iter().map(|item| item.into()).collect()
+        }
+
+        fn entries(
+            account: AccountId,
+        ) -> Vec<pallet_robonomics_datalog::Entry<AccountId, Moment, DatalogRecord>> {
+            Datalog::entries(&account)
+        }
+    }
+
+    impl pallet_robonomics_datalog_rpc_runtime_api::DatalogMmrApi<
+        Block,
+        AccountId,
+        Hash,
+    > for Runtime {
+        fn mmr_root(account: AccountId) -> Hash {
+            Datalog::mmr_root(account)
+        }
+
+        fn mmr_leaves(account: AccountId) -> u64 {
+            Datalog::mmr_leaves(account)
+        }
+
+        fn mmr_peaks(account: AccountId) -> Vec<Hash> {
+            Datalog::mmr_peaks(account)
+        }
+    }
+
+    impl pallet_robonomics_datalog_rpc_runtime_api::DatalogBlockApi<
+        Block,
+        AccountId,
+        BlockNumber,
+    > for Runtime {
+        fn record_block(account: AccountId, seq: u64) -> Option<BlockNumber> {
+            Datalog::block_of(&account, seq)
+        }
+    }
+
+    impl pallet_robonomics_datalog_rpc_runtime_api::DatalogEventsApi<
+        Block,
+        AccountId,
+        Moment,
+        DatalogRecord,
+    > for Runtime {
+        fn record_changes(
+        ) -> Vec<pallet_robonomics_datalog::RecordChange<AccountId, Moment, DatalogRecord>> {
+            System::events()
+                .into_iter()
+                .filter_map(|record| match record.event {
+                    Event::pallet_robonomics_datalog(event) => event.record_change(),
+                    _ => None,
+                })
+                .collect()
+        }
+    }
+
+    impl pallet_carbon_assets_runtime_api::CarbonAssetsApi<
+        Block,
+        AccountId,
+        CarbonAssetId,
+        Balance,
+        BlockNumber,
+    > for Runtime {
+        fn class(
+            class: CarbonAssetId,
+        ) -> Option<pallet_carbon_assets::AssetClass<AccountId, Balance>> {
+            CarbonAssets::class(class)
+        }
+
+        fn balance(class: CarbonAssetId, who: AccountId) -> Balance {
+            CarbonAssets::balance(class, who)
+        }
+
+        fn certificate(
+            id: u64,
+        ) -> Option<pallet_carbon_assets::CertificateOf<Runtime>> {
+            CarbonAssets::certificate(id)
+        }
+
+        fn certificates_of(
+            beneficiary: AccountId,
+        ) -> Vec<(u64, pallet_carbon_assets::CertificateOf<Runtime>)> {
+            CarbonAssets::certificates_of(&beneficiary)
+        }
+    }
+
     impl sp_session::SessionKeys<Block> for Runtime {
         fn generate_session_keys(seed: Option<Vec<u8>>) -> Vec<u8> {
             SessionKeys::generate(seed)
@@ -822,15 +1347,158 @@ impl_runtime_apis! {
             add_benchmark!(params, batches, pallet_im_online, ImOnline);
             add_benchmark!(params, batches, pallet_indices, Indices);
             add_benchmark!(params, batches, pallet_multisig, Multisig);
+            add_benchmark!(params, batches, pallet_proxy, Proxy);
             add_benchmark!(params, batches, pallet_offences, OffencesBench::<Runtime>);
             add_benchmark!(params, batches, pallet_session, SessionBench::<Runtime>);
             add_benchmark!(params, batches, pallet_staking, Staking);
             add_benchmark!(params, batches, frame_system, SystemBench::<Runtime>);
             add_benchmark!(params, batches, pallet_timestamp, Timestamp);
             add_benchmark!(params, batches, pallet_utility, Utility);
+            add_benchmark!(params, batches, pallet_robonomics_datalog, Datalog);
+            add_benchmark!(params, batches, pallet_carbon_assets, CarbonAssets);
+            add_benchmark!(params, batches, pallet_carbon_projects, CarbonProjects);
+            add_benchmark!(params, batches, pallet_collective, Council);
+            add_benchmark!(params, batches, pallet_democracy, Democracy);
+            add_benchmark!(params, batches, pallet_scheduler, Scheduler);
+            add_benchmark!(params, batches, pallet_treasury, Treasury);
 
             if batches.is_empty() { return Err("Benchmark not found for this pallet.".into()) }
             Ok(batches)
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pallet_robonomics_datalog::WeightInfo as _;
+    use sp_runtime::DispatchError;
+
+    type DatalogWeight = <Runtime as pallet_robonomics_datalog::Trait>::WeightInfo;
+
+    fn account(seed: u8) -> AccountId {
+        AccountId::from([seed; 32])
+    }
+
+    fn new_test_ext() -> sp_io::TestExternalities {
+        let mut storage = frame_system::GenesisConfig::default()
+            .build_storage::<Runtime>()
+            .unwrap();
+        pallet_balances::GenesisConfig::<Runtime> {
+            balances: (1..4).map(|seed| (account(seed), 1_000 * MITO)).collect(),
+        }
+        .assimilate_storage(&mut storage)
+        .unwrap();
+        let mut ext: sp_io::TestExternalities = storage.into();
+        // events are deposited since the first block
+        ext.execute_with(|| System::set_block_number(1));
+        ext
+    }
+
+    fn last_event() -> Event {
+        System::events().pop().expect("event expected").event
+    }
+
+    #[test]
+    fn datalog_window_calls_fit_into_extrinsic() {
+        let window = DatalogMaximumWindowSize::get() as u32;
+        let max = MaximumExtrinsicWeight::get();
+
+        assert!(DatalogWeight::set_window_size(window) <= max);
+        assert!(DatalogWeight::erase(window) <= max);
+        assert!(DatalogWeight::force_erase(window, window) <= max);
+        assert!(DatalogWeight::erase_older_than(window) <= max);
+        assert!(DatalogWeight::erase_item(window / 2) <= max);
+    }
+
+    #[test]
+    fn non_transfer_proxy_filter() {
+        new_test_ext().execute_with(|| {
+            let (real, delegate) = (account(1), account(2));
+            assert!(Proxy::add_proxy(
+                Origin::signed(real.clone()),
+                delegate.clone(),
+                ProxyType::NonTransfer,
+                0
+            )
+            .is_ok());
+
+            let filtered: Event =
+                pallet_proxy::Event::<Runtime>::ProxyExecuted(Err(DispatchError::BadOrigin)).into();
+            let calls = vec![
+                Call::Proxy(pallet_proxy::Call::add_proxy(
+                    account(3),
+                    ProxyType::AssetRegistry,
+                    0,
+                )),
+                Call::CarbonAssets(pallet_carbon_assets::Call::transfer(
+                    0,
+                    Indices::unlookup(account(3)),
+                    1,
+                )),
+                Call::CarbonAssets(pallet_carbon_assets::Call::retire(
+                    0,
+                    1,
+                    Indices::unlookup(account(3)),
+                    b"offset".to_vec(),
+                    None,
+                )),
+            ];
+            for call in calls {
+                assert!(Proxy::proxy(
+                    Origin::signed(delegate.clone()),
+                    real.clone(),
+                    None,
+                    Box::new(call)
+                )
+                .is_ok());
+                assert_eq!(last_event(), filtered);
+            }
+            assert_eq!(Proxy::proxies(&real).0.len(), 1);
+
+            // proxies of narrower types are allowed
+            let call = pallet_proxy::Call::add_proxy(account(3), ProxyType::Staking, 0);
+            assert!(Proxy::proxy(
+                Origin::signed(delegate),
+                real.clone(),
+                None,
+                Box::new(Call::Proxy(call))
+            )
+            .is_ok());
+            let executed: Event = pallet_proxy::Event::<Runtime>::ProxyExecuted(Ok(())).into();
+            assert_eq!(last_event(), executed);
+            assert_eq!(Proxy::proxies(&real).0.len(), 2);
+        })
+    }
+
+    #[test]
+    fn council_schedules_named_tasks() {
+        new_test_ext().execute_with(|| {
+            let council = |yes, all| -> Origin {
+                pallet_collective::RawOrigin::<AccountId, CouncilCollective>::Members(yes, all)
+                    .into()
+            };
+            let task = b"retention".to_vec();
+            let call = || Box::new(Call::System(frame_system::Call::remark(vec![])));
+
+            assert_eq!(
+                Scheduler::schedule_named(council(1, 2), task.clone(), 10, None, 0, call()),
+                Err(DispatchError::BadOrigin)
+            );
+            assert!(
+                Scheduler::schedule_named(council(2, 3), task.clone(), 10, None, 0, call()).is_ok()
+            );
+            let scheduled: Event = pallet_scheduler::Event::<Runtime>::Scheduled(10, 0).into();
+            assert_eq!(last_event(), scheduled);
+
+            assert_eq!(
+                Scheduler::cancel_named(council(1, 2), task.clone()),
+                Err(DispatchError::BadOrigin)
+            );
+            assert!(Scheduler::cancel_named(council(2, 3), task.clone()).is_ok());
+            let canceled: Event = pallet_scheduler::Event::<Runtime>::Canceled(10, 0).into();
+            assert_eq!(last_event(), canceled);
+            assert!(Scheduler::cancel_named(council(2, 3), task).is_err());
+        })
+    }
+}
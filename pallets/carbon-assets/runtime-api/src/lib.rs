@@ -0,0 +1,43 @@
+///////////////////////////////////////////////////////////////////////////////
+//
+//  Copyright 2018-2020 Airalab <research@aira.life>
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+///////////////////////////////////////////////////////////////////////////////
+//! Runtime API definition for carbon assets and retirement certificates queries.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+pub use pallet_carbon_assets::{AssetClass, Certificate};
+use sp_std::prelude::*;
+
+sp_api::decl_runtime_apis! {
+    pub trait CarbonAssetsApi<AccountId, AssetId, Balance, BlockNumber> where
+        AccountId: Codec,
+        AssetId: Codec,
+        Balance: Codec,
+        BlockNumber: Codec,
+    {
+        /// Asset class with its supply.
+        fn class(class: AssetId) -> Option<AssetClass<AccountId, Balance>>;
+        /// Units of asset class held by account.
+        fn balance(class: AssetId, who: AccountId) -> Balance;
+        /// Retirement certificate by its identifier.
+        fn certificate(id: u64) -> Option<Certificate<AccountId, AssetId, Balance, BlockNumber>>;
+        /// Retirement certificates of beneficiary with their identifiers.
+        fn certificates_of(
+            beneficiary: AccountId,
+        ) -> Vec<(u64, Certificate<AccountId, AssetId, Balance, BlockNumber>)>;
+    }
+}
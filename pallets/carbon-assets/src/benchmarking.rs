@@ -0,0 +1,140 @@
+///////////////////////////////////////////////////////////////////////////////
+//
+//  Copyright 2018-2020 Airalab <research@aira.life>
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+///////////////////////////////////////////////////////////////////////////////
+//! Carbon assets pallet benchmarking.
+#![cfg(feature = "runtime-benchmarks")]
+
+use super::*;
+use frame_benchmarking::{account, benchmarks, whitelisted_caller};
+use frame_support::{sp_runtime::traits::Bounded, sp_std::vec};
+use frame_system::RawOrigin;
+
+/// Create asset class owned by `owner` with `owner` as its issuer.
+fn create_class<T: Trait>(owner: &T::AccountId) -> Result<T::AssetId, &'static str> {
+    let project = vec![0x42; T::MaxProjectLength::get() as usize];
+    let class = Module::<T>::create(owner.clone(), project, 2020, One::one())?;
+    Issuers::<T>::insert(class, owner, true);
+    Ok(class)
+}
+
+benchmarks! {
+    _ { }
+
+    create_class {
+        let owner: T::AccountId = account("owner", 0, 0);
+        let project = vec![0x42; T::MaxProjectLength::get() as usize];
+        let class = NextClassId::<T>::get();
+    }: {
+        Module::<T>::create_class(
+            T::RegistrarOrigin::successful_origin(),
+            T::Lookup::unlookup(owner),
+            project,
+            2020,
+            One::one(),
+        )?;
+    }
+    verify {
+        assert!(Classes::<T>::contains_key(class));
+    }
+
+    set_owner {
+        let caller: T::AccountId = whitelisted_caller();
+        let class = create_class::<T>(&caller)?;
+        let owner: T::AccountId = account("owner", 0, 0);
+    }: _(RawOrigin::Signed(caller), class, T::Lookup::unlookup(owner.clone()))
+    verify {
+        assert_eq!(Classes::<T>::get(class).map(|info| info.owner), Some(owner));
+    }
+
+    add_issuer {
+        let caller: T::AccountId = whitelisted_caller();
+        let class = create_class::<T>(&caller)?;
+        let issuer: T::AccountId = account("issuer", 0, 0);
+    }: _(RawOrigin::Signed(caller), class, issuer.clone())
+    verify {
+        assert!(Issuers::<T>::get(class, &issuer));
+    }
+
+    remove_issuer {
+        let caller: T::AccountId = whitelisted_caller();
+        let class = create_class::<T>(&caller)?;
+        let issuer: T::AccountId = account("issuer", 0, 0);
+        Issuers::<T>::insert(class, &issuer, true);
+    }: _(RawOrigin::Signed(caller), class, issuer.clone())
+    verify {
+        assert!(!Issuers::<T>::get(class, &issuer));
+    }
+
+    mint {
+        let caller: T::AccountId = whitelisted_caller();
+        let class = create_class::<T>(&caller)?;
+        let beneficiary: T::AccountId = account("beneficiary", 0, 0);
+        let amount: T::Balance = 1_000u32.into();
+    }: _(RawOrigin::Signed(caller), class, T::Lookup::unlookup(beneficiary.clone()), amount)
+    verify {
+        assert_eq!(Balances::<T>::get(class, &beneficiary), amount);
+    }
+
+    transfer {
+        let caller: T::AccountId = whitelisted_caller();
+        let class = create_class::<T>(&caller)?;
+        let dest: T::AccountId = account("dest", 0, 0);
+        let amount: T::Balance = 1_000u32.into();
+        Module::<T>::issue(class, &caller, amount)?;
+        Module::<T>::issue(class, &dest, amount)?;
+    }: _(RawOrigin::Signed(caller), class, T::Lookup::unlookup(dest.clone()), amount)
+    verify {
+        assert_eq!(Balances::<T>::get(class, &dest), amount + amount);
+    }
+
+    retire {
+        let p in 0 .. T::MaxPurposeLength::get();
+        let caller: T::AccountId = whitelisted_caller();
+        let class = create_class::<T>(&caller)?;
+        let beneficiary: T::AccountId = account("beneficiary", 0, 0);
+        let amount: T::Balance = 1_000u32.into();
+        Module::<T>::issue(class, &caller, amount)?;
+        T::Currency::make_free_balance_be(&caller, FeeOf::<T>::max_value() / 2u32.into());
+        T::RecordHistory::note_record(&caller, 0);
+        let datalog = Some((caller.clone(), 0));
+        let dest = T::Lookup::unlookup(beneficiary.clone());
+        let purpose = vec![0x42; p as usize];
+    }: _(RawOrigin::Signed(caller), class, amount, dest, purpose, datalog)
+    verify {
+        assert_eq!(Module::<T>::certificates_of(&beneficiary).len(), 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{new_test_ext, Runtime};
+    use frame_support::assert_ok;
+
+    #[test]
+    fn test_benchmarks() {
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_create_class::<Runtime>());
+            assert_ok!(test_benchmark_set_owner::<Runtime>());
+            assert_ok!(test_benchmark_add_issuer::<Runtime>());
+            assert_ok!(test_benchmark_remove_issuer::<Runtime>());
+            assert_ok!(test_benchmark_mint::<Runtime>());
+            assert_ok!(test_benchmark_transfer::<Runtime>());
+            assert_ok!(test_benchmark_retire::<Runtime>());
+        });
+    }
+}
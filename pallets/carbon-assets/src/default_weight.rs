@@ -0,0 +1,80 @@
+///////////////////////////////////////////////////////////////////////////////
+//
+//  Copyright 2018-2020 Airalab <research@aira.life>
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+///////////////////////////////////////////////////////////////////////////////
+//! Weights for pallet_carbon_assets
+//!
+//! These are estimates derived from storage access of every call, not benchmark results.
+//! Replace them with output of `benchmarking.rs` on reference hardware:
+//!
+//! ipci benchmark --chain dev --execution wasm --wasm-execution compiled \
+//!     --pallet pallet_carbon_assets --extrinsic '*' --steps 50 --repeat 20
+//!
+//! with the node built using `--features runtime-benchmarks`.
+
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::weights::{constants::RocksDbWeight as DbWeight, Weight};
+
+pub trait WeightInfo {
+    fn create_class() -> Weight;
+    fn set_owner() -> Weight;
+    fn add_issuer() -> Weight;
+    fn remove_issuer() -> Weight;
+    fn mint() -> Weight;
+    fn transfer() -> Weight;
+    fn retire(p: u32) -> Weight;
+}
+
+impl WeightInfo for () {
+    fn create_class() -> Weight {
+        (31_470_000 as Weight)
+            .saturating_add(DbWeight::get().reads(1 as Weight))
+            .saturating_add(DbWeight::get().writes(2 as Weight))
+    }
+    fn set_owner() -> Weight {
+        (27_930_000 as Weight)
+            .saturating_add(DbWeight::get().reads(1 as Weight))
+            .saturating_add(DbWeight::get().writes(1 as Weight))
+    }
+    fn add_issuer() -> Weight {
+        (25_610_000 as Weight)
+            .saturating_add(DbWeight::get().reads(1 as Weight))
+            .saturating_add(DbWeight::get().writes(1 as Weight))
+    }
+    fn remove_issuer() -> Weight {
+        (26_080_000 as Weight)
+            .saturating_add(DbWeight::get().reads(1 as Weight))
+            .saturating_add(DbWeight::get().writes(1 as Weight))
+    }
+    fn mint() -> Weight {
+        (44_720_000 as Weight)
+            .saturating_add(DbWeight::get().reads(3 as Weight))
+            .saturating_add(DbWeight::get().writes(2 as Weight))
+    }
+    fn transfer() -> Weight {
+        (48_350_000 as Weight)
+            .saturating_add(DbWeight::get().reads(3 as Weight))
+            .saturating_add(DbWeight::get().writes(2 as Weight))
+    }
+    fn retire(p: u32) -> Weight {
+        (62_190_000 as Weight)
+            .saturating_add((1_000 as Weight).saturating_mul(p as Weight))
+            .saturating_add(DbWeight::get().reads(5 as Weight))
+            .saturating_add(DbWeight::get().writes(6 as Weight))
+    }
+}
@@ -0,0 +1,912 @@
+///////////////////////////////////////////////////////////////////////////////
+//
+//  Copyright 2018-2020 Airalab <research@aira.life>
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+///////////////////////////////////////////////////////////////////////////////
+//! Emission reduction asset registry runtime module.
+//!
+//! Every asset class groups units of one emission reduction project and vintage year.
+//! Classes are created by registrar origin, class owner appoints issuers allowed to mint
+//! its units, units are transferable between accounts. Account holds either no units
+//! or at least minimal balance of the class, so dust entries can't bloat the storage.
+//! Retired units are burned and a public retirement certificate is stored for the beneficiary,
+//! certificate is permanent, so fee for its storage is burned from the retiring account.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use frame_support::{
+    codec::{Decode, Encode},
+    decl_error, decl_event, decl_module, decl_storage, ensure, IterableStorageDoubleMap,
+    Parameter,
+    sp_runtime::{
+        traits::{
+            AtLeast32BitUnsigned, CheckedAdd, CheckedSub, MaybeSerializeDeserialize, Member, One,
+            Saturating, StaticLookup, Zero,
+        },
+        DispatchResult,
+    },
+    sp_std::prelude::*,
+    traits::{Currency, EnsureOrigin, ExistenceRequirement, Get, WithdrawReason},
+};
+use frame_system::ensure_signed;
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+
+pub use default_weight::WeightInfo;
+mod benchmarking;
+mod default_weight;
+
+/// Emission reduction asset class, units of one project and vintage year.
+#[cfg_attr(feature = "std", derive(Debug, PartialEq, Serialize, Deserialize))]
+#[derive(Encode, Decode, Default, Clone)]
+pub struct AssetClass<AccountId, Balance> {
+    /// Account appointing issuers of the class.
+    pub owner: AccountId,
+    /// Identifier of emission reduction project.
+    pub project: Vec<u8>,
+    /// Year emission reductions were achieved in.
+    pub vintage: u16,
+    /// Units in circulation.
+    pub supply: Balance,
+    /// Minimal positive balance of account.
+    pub min_balance: Balance,
+}
+
+/// Public record of units retired on behalf of beneficiary.
+#[cfg_attr(feature = "std", derive(Debug, PartialEq, Serialize, Deserialize))]
+#[derive(Encode, Decode, Clone)]
+pub struct Certificate<AccountId, AssetId, Balance, BlockNumber> {
+    /// Asset class of retired units.
+    pub class: AssetId,
+    /// Account the units were burned from.
+    pub retired_by: AccountId,
+    /// Account emission of which is offset.
+    pub beneficiary: AccountId,
+    /// Amount of retired units.
+    pub amount: Balance,
+    /// Purpose of retirement.
+    pub purpose: Vec<u8>,
+    /// Datalog record supporting the retirement: [datalog owner, sequence number].
+    pub datalog: Option<(AccountId, u64)>,
+    /// Block the units were retired in.
+    pub block: BlockNumber,
+}
+
+/// Balance of fee currency.
+pub type FeeOf<T> =
+    <<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::Balance;
+
+/// Datalog history retirement certificates and monitoring reports refer to.
+pub trait RecordHistory<AccountId> {
+    /// Whether `seq`-th record of account has ever been written.
+    fn contains(account: &AccountId, seq: u64) -> bool;
+
+    /// Make sure records of account up to `seq`-th one are written.
+    #[cfg(feature = "runtime-benchmarks")]
+    fn note_record(account: &AccountId, seq: u64);
+}
+
+impl<AccountId> RecordHistory<AccountId> for () {
+    fn contains(_account: &AccountId, _seq: u64) -> bool {
+        true
+    }
+
+    #[cfg(feature = "runtime-benchmarks")]
+    fn note_record(_account: &AccountId, _seq: u64) {}
+}
+
+/// Retirement certificate of runtime.
+pub type CertificateOf<T> = Certificate<
+    <T as frame_system::Trait>::AccountId,
+    <T as Trait>::AssetId,
+    <T as Trait>::Balance,
+    <T as frame_system::Trait>::BlockNumber,
+>;
+
+/// Carbon assets module main trait.
+pub trait Trait: frame_system::Trait {
+    /// The overarching event type.
+    type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
+    /// Asset class identifier.
+    type AssetId: Parameter
+        + Member
+        + AtLeast32BitUnsigned
+        + Default
+        + Copy
+        + MaybeSerializeDeserialize;
+    /// Balance of asset units.
+    type Balance: Parameter
+        + Member
+        + AtLeast32BitUnsigned
+        + Default
+        + Copy
+        + MaybeSerializeDeserialize;
+    /// Origin allowed to create asset classes.
+    type RegistrarOrigin: EnsureOrigin<Self::Origin>;
+    /// Maximum length of project identifier.
+    type MaxProjectLength: Get<u32>;
+    /// Maximum length of retirement purpose.
+    type MaxPurposeLength: Get<u32>;
+    /// Datalog history checked for records referred to by certificates.
+    type RecordHistory: RecordHistory<Self::AccountId>;
+    /// Currency of certificate fees.
+    type Currency: Currency<Self::AccountId>;
+    /// Fee burned for every retirement certificate.
+    type CertificateFeeBase: Get<FeeOf<Self>>;
+    /// Fee burned for every byte of retirement purpose.
+    type CertificateFeePerByte: Get<FeeOf<Self>>;
+    /// extrinsic weights
+    type WeightInfo: WeightInfo;
+}
+
+decl_error! {
+    pub enum Error for Module<T: Trait> {
+        /// Asset class doesn't exist.
+        UnknownClass,
+        /// Sender is not owner of asset class.
+        NotOwner,
+        /// Sender is not allowed to issue units of asset class.
+        NotIssuer,
+        /// Account has not enough units.
+        InsufficientBalance,
+        /// Amount of units should be positive.
+        ZeroAmount,
+        /// Supply or class counter overflow.
+        Overflow,
+        /// Project identifier is longer than allowed.
+        ProjectTooLong,
+        /// Retirement purpose is longer than allowed.
+        PurposeTooLong,
+        /// Balance would be positive but below minimal balance of asset class.
+        BelowMinimum,
+        /// Referred datalog record has never been written.
+        UnknownRecord,
+        /// Account can't pay certificate fee and stay alive.
+        InsufficientFee,
+    }
+}
+
+decl_event! {
+    pub enum Event<T>
+    where AccountId = <T as frame_system::Trait>::AccountId,
+          AssetId = <T as Trait>::AssetId,
+          Balance = <T as Trait>::Balance,
+    {
+        /// Asset class created: [class, owner].
+        ClassCreated(AssetId, AccountId),
+        /// Asset class owner changed: [class, new owner].
+        OwnerChanged(AssetId, AccountId),
+        /// Issuer appointed: [class, issuer].
+        IssuerAdded(AssetId, AccountId),
+        /// Issuer dismissed: [class, issuer].
+        IssuerRemoved(AssetId, AccountId),
+        /// Units issued: [class, beneficiary, amount].
+        Issued(AssetId, AccountId, Balance),
+        /// Units transferred: [class, from, to, amount].
+        Transferred(AssetId, AccountId, AccountId, Balance),
+        /// Units retired: [class, retired by, beneficiary, amount, certificate].
+        Retired(AssetId, AccountId, AccountId, Balance, u64),
+    }
+}
+
+decl_storage! {
+    trait Store for Module<T: Trait> as CarbonAssets {
+        /// Identifier of the next asset class.
+        NextClassId get(fn next_class_id): T::AssetId;
+        /// Asset classes.
+        Classes get(fn class): map hasher(twox_64_concat)
+                                 T::AssetId => Option<AssetClass<T::AccountId, T::Balance>>;
+        /// Accounts allowed to issue units of asset class.
+        Issuers get(fn is_issuer): double_map hasher(twox_64_concat) T::AssetId,
+                                 hasher(blake2_128_concat) T::AccountId => bool;
+        /// Units of asset class held by account.
+        Balances get(fn balance): double_map hasher(twox_64_concat) T::AssetId,
+                                 hasher(blake2_128_concat) T::AccountId => T::Balance;
+        /// Identifier of the next retirement certificate.
+        NextCertificateId get(fn next_certificate_id): u64;
+        /// Retirement certificates.
+        Certificates get(fn certificate): map hasher(twox_64_concat)
+                                 u64 => Option<CertificateOf<T>>;
+        /// Certificates of beneficiary.
+        BeneficiaryCertificates: double_map hasher(blake2_128_concat) T::AccountId,
+                                 hasher(twox_64_concat) u64 => ();
+    }
+    add_extra_genesis {
+        /// Asset classes: [owner, project, vintage, minimal balance, issuers].
+        config(classes): Vec<(T::AccountId, Vec<u8>, u16, T::Balance, Vec<T::AccountId>)>;
+        build(|config: &GenesisConfig<T>| {
+            for (owner, project, vintage, min_balance, issuers) in config.classes.iter() {
+                let class = Module::<T>::create(
+                    owner.clone(),
+                    project.clone(),
+                    *vintage,
+                    *min_balance,
+                )
+                .expect("genesis asset class should be valid");
+                for issuer in issuers.iter() {
+                    Issuers::<T>::insert(class, issuer, true);
+                }
+            }
+        })
+    }
+}
+
+decl_module! {
+    pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+        type Error = Error<T>;
+
+        fn deposit_event() = default;
+
+        /// Create asset class of `project` units achieved in `vintage` year.
+        ///
+        /// Accounts should hold at least `min_balance` units of the class, or none.
+        #[weight = T::WeightInfo::create_class()]
+        fn create_class(
+            origin,
+            owner: <T::Lookup as StaticLookup>::Source,
+            project: Vec<u8>,
+            vintage: u16,
+            #[compact] min_balance: T::Balance,
+        ) {
+            T::RegistrarOrigin::ensure_origin(origin)?;
+            let owner = T::Lookup::lookup(owner)?;
+
+            Self::create(owner, project, vintage, min_balance)?;
+        }
+
+        /// Pass ownership of asset class to another account.
+        #[weight = T::WeightInfo::set_owner()]
+        fn set_owner(origin, class: T::AssetId, owner: <T::Lookup as StaticLookup>::Source) {
+            let sender = ensure_signed(origin)?;
+            let owner = T::Lookup::lookup(owner)?;
+
+            Classes::<T>::try_mutate(class, |info| -> DispatchResult {
+                let info = info.as_mut().ok_or(Error::<T>::UnknownClass)?;
+                ensure!(info.owner == sender, Error::<T>::NotOwner);
+                info.owner = owner.clone();
+                Ok(())
+            })?;
+            Self::deposit_event(RawEvent::OwnerChanged(class, owner));
+        }
+
+        /// Allow `issuer` to issue units of asset class.
+        #[weight = T::WeightInfo::add_issuer()]
+        fn add_issuer(origin, class: T::AssetId, issuer: T::AccountId) {
+            let sender = ensure_signed(origin)?;
+            Self::ensure_owner(class, &sender)?;
+
+            Issuers::<T>::insert(class, &issuer, true);
+            Self::deposit_event(RawEvent::IssuerAdded(class, issuer));
+        }
+
+        /// Dismiss `issuer` of asset class.
+        #[weight = T::WeightInfo::remove_issuer()]
+        fn remove_issuer(origin, class: T::AssetId, issuer: T::AccountId) {
+            let sender = ensure_signed(origin)?;
+            Self::ensure_owner(class, &sender)?;
+
+            Issuers::<T>::remove(class, &issuer);
+            Self::deposit_event(RawEvent::IssuerRemoved(class, issuer));
+        }
+
+        /// Issue `amount` units of asset class to `beneficiary`.
+        #[weight = T::WeightInfo::mint()]
+        fn mint(
+            origin,
+            class: T::AssetId,
+            beneficiary: <T::Lookup as StaticLookup>::Source,
+            #[compact] amount: T::Balance,
+        ) {
+            let sender = ensure_signed(origin)?;
+            ensure!(Issuers::<T>::get(class, &sender), Error::<T>::NotIssuer);
+            let beneficiary = T::Lookup::lookup(beneficiary)?;
+
+            Self::issue(class, &beneficiary, amount)?;
+        }
+
+        /// Transfer `amount` units of asset class to `dest`.
+        ///
+        /// Both accounts should be left with no units or at least minimal balance of the class.
+        #[weight = T::WeightInfo::transfer()]
+        fn transfer(
+            origin,
+            class: T::AssetId,
+            dest: <T::Lookup as StaticLookup>::Source,
+            #[compact] amount: T::Balance,
+        ) {
+            let sender = ensure_signed(origin)?;
+            let dest = T::Lookup::lookup(dest)?;
+            ensure!(!amount.is_zero(), Error::<T>::ZeroAmount);
+            let info = Classes::<T>::get(class).ok_or(Error::<T>::UnknownClass)?;
+
+            let balance = Balances::<T>::get(class, &sender)
+                .checked_sub(&amount)
+                .ok_or(Error::<T>::InsufficientBalance)?;
+            if sender != dest {
+                let dest_balance = Balances::<T>::get(class, &dest)
+                    .checked_add(&amount)
+                    .ok_or(Error::<T>::Overflow)?;
+                Self::ensure_min_balance(&info, balance)?;
+                Self::ensure_min_balance(&info, dest_balance)?;
+                Self::set_balance(class, &sender, balance);
+                Self::set_balance(class, &dest, dest_balance);
+            }
+            Self::deposit_event(RawEvent::Transferred(class, sender, dest, amount));
+        }
+
+        /// Burn `amount` units of asset class offsetting emission of `beneficiary`.
+        ///
+        /// Certificate refers to datalog record supporting the retirement if given.
+        /// Certificate is permanent, so fee for its storage is burned rather than reserved.
+        #[weight = T::WeightInfo::retire(purpose.len() as u32)]
+        fn retire(
+            origin,
+            class: T::AssetId,
+            #[compact] amount: T::Balance,
+            beneficiary: <T::Lookup as StaticLookup>::Source,
+            purpose: Vec<u8>,
+            datalog: Option<(T::AccountId, u64)>,
+        ) {
+            let sender = ensure_signed(origin)?;
+            let beneficiary = T::Lookup::lookup(beneficiary)?;
+            ensure!(
+                purpose.len() <= T::MaxPurposeLength::get() as usize,
+                Error::<T>::PurposeTooLong
+            );
+            if let Some((account, seq)) = &datalog {
+                ensure!(T::RecordHistory::contains(account, *seq), Error::<T>::UnknownRecord);
+            }
+            let fee = T::CertificateFeeBase::get().saturating_add(
+                T::CertificateFeePerByte::get().saturating_mul((purpose.len() as u32).into()),
+            );
+            // units are burned first, so the fee is checked upfront
+            let new_balance = T::Currency::free_balance(&sender)
+                .checked_sub(&fee)
+                .filter(|balance| *balance >= T::Currency::minimum_balance())
+                .ok_or(Error::<T>::InsufficientFee)?;
+            T::Currency::ensure_can_withdraw(&sender, fee, WithdrawReason::Fee.into(), new_balance)
+                .map_err(|_| Error::<T>::InsufficientFee)?;
+
+            Self::burn(class, &sender, amount)?;
+            // dropped imbalance reduces total issuance
+            let _ = T::Currency::withdraw(
+                &sender,
+                fee,
+                WithdrawReason::Fee.into(),
+                ExistenceRequirement::KeepAlive,
+            )?;
+            let id = NextCertificateId::get();
+            let certificate = Certificate {
+                class,
+                retired_by: sender.clone(),
+                beneficiary: beneficiary.clone(),
+                amount,
+                purpose,
+                datalog,
+                block: <frame_system::Module<T>>::block_number(),
+            };
+            Certificates::<T>::insert(id, certificate);
+            BeneficiaryCertificates::<T>::insert(&beneficiary, id, ());
+            NextCertificateId::put(id.saturating_add(1));
+
+            Self::deposit_event(RawEvent::Retired(class, sender, beneficiary, amount, id));
+        }
+    }
+}
+
+impl<T: Trait> Module<T> {
+    /// Register new asset class and return its identifier, `ClassCreated` is emitted.
+    pub fn create(
+        owner: T::AccountId,
+        project: Vec<u8>,
+        vintage: u16,
+        min_balance: T::Balance,
+    ) -> Result<T::AssetId, Error<T>> {
+        ensure!(
+            project.len() <= T::MaxProjectLength::get() as usize,
+            Error::<T>::ProjectTooLong
+        );
+        ensure!(!min_balance.is_zero(), Error::<T>::ZeroAmount);
+
+        let class = NextClassId::<T>::get();
+        let next = class.checked_add(&One::one()).ok_or(Error::<T>::Overflow)?;
+        NextClassId::<T>::put(next);
+        let info = AssetClass {
+            owner: owner.clone(),
+            project,
+            vintage,
+            supply: Zero::zero(),
+            min_balance,
+        };
+        Classes::<T>::insert(class, info);
+        Self::deposit_event(RawEvent::ClassCreated(class, owner));
+        Ok(class)
+    }
+
+    /// Make sure account balance is either zero or not below minimal balance of asset class.
+    fn ensure_min_balance(
+        info: &AssetClass<T::AccountId, T::Balance>,
+        balance: T::Balance,
+    ) -> DispatchResult {
+        ensure!(
+            balance.is_zero() || balance >= info.min_balance,
+            Error::<T>::BelowMinimum
+        );
+        Ok(())
+    }
+
+    /// Store account balance, accounts without units don't occupy storage.
+    fn set_balance(class: T::AssetId, who: &T::AccountId, balance: T::Balance) {
+        if balance.is_zero() {
+            Balances::<T>::remove(class, who);
+        } else {
+            Balances::<T>::insert(class, who, balance);
+        }
+    }
+
+    fn ensure_owner(class: T::AssetId, who: &T::AccountId) -> DispatchResult {
+        let info = Classes::<T>::get(class).ok_or(Error::<T>::UnknownClass)?;
+        ensure!(&info.owner == who, Error::<T>::NotOwner);
+        Ok(())
+    }
+
+    /// Issue `amount` units of asset class to `beneficiary` increasing class supply.
+    pub fn issue(class: T::AssetId, beneficiary: &T::AccountId, amount: T::Balance) -> DispatchResult {
+        ensure!(!amount.is_zero(), Error::<T>::ZeroAmount);
+        Classes::<T>::try_mutate(class, |info| -> DispatchResult {
+            let info = info.as_mut().ok_or(Error::<T>::UnknownClass)?;
+            let balance = Balances::<T>::get(class, beneficiary)
+                .checked_add(&amount)
+                .ok_or(Error::<T>::Overflow)?;
+            Self::ensure_min_balance(info, balance)?;
+            info.supply = info.supply.checked_add(&amount).ok_or(Error::<T>::Overflow)?;
+            Self::set_balance(class, beneficiary, balance);
+            Ok(())
+        })?;
+
+        Self::deposit_event(RawEvent::Issued(class, beneficiary.clone(), amount));
+        Ok(())
+    }
+
+    /// Burn `amount` units of asset class held by `who` decreasing class supply.
+    fn burn(class: T::AssetId, who: &T::AccountId, amount: T::Balance) -> DispatchResult {
+        ensure!(!amount.is_zero(), Error::<T>::ZeroAmount);
+        Classes::<T>::try_mutate(class, |info| -> DispatchResult {
+            let info = info.as_mut().ok_or(Error::<T>::UnknownClass)?;
+            let balance = Balances::<T>::get(class, who)
+                .checked_sub(&amount)
+                .ok_or(Error::<T>::InsufficientBalance)?;
+            Self::ensure_min_balance(info, balance)?;
+            info.supply = info.supply.saturating_sub(amount);
+            Self::set_balance(class, who, balance);
+            Ok(())
+        })
+    }
+
+    /// Retirement certificates of beneficiary with their identifiers.
+    pub fn certificates_of(beneficiary: &T::AccountId) -> Vec<(u64, CertificateOf<T>)> {
+        BeneficiaryCertificates::<T>::iter_prefix(beneficiary)
+            .filter_map(|(id, _)| Certificates::<T>::get(id).map(|certificate| (id, certificate)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use frame_support::{
+        assert_noop, assert_ok, impl_outer_event, impl_outer_origin, parameter_types,
+        sp_runtime::{
+            testing::Header,
+            traits::{BlakeTwo256, IdentityLookup},
+            DispatchError, Perbill,
+        },
+        weights::Weight,
+    };
+    use sp_core::H256;
+
+    impl_outer_origin! {
+        pub enum Origin for Runtime {}
+    }
+
+    mod carbon_assets {
+        pub use crate::Event;
+    }
+
+    impl_outer_event! {
+        pub enum TestEvent for Runtime {
+            frame_system<T>,
+            pallet_balances<T>,
+            carbon_assets<T>,
+        }
+    }
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    pub struct Runtime;
+
+    parameter_types! {
+        pub const BlockHashCount: u64 = 250;
+        pub const MaximumBlockWeight: Weight = 1024;
+        pub const MaximumBlockLength: u32 = 2 * 1024;
+        pub const AvailableBlockRatio: Perbill = Perbill::one();
+    }
+
+    impl frame_system::Trait for Runtime {
+        type BaseCallFilter = ();
+        type Origin = Origin;
+        type Index = u64;
+        type BlockNumber = u64;
+        type Call = ();
+        type Hash = H256;
+        type Hashing = BlakeTwo256;
+        type AccountId = u64;
+        type Lookup = IdentityLookup<Self::AccountId>;
+        type Header = Header;
+        type Event = TestEvent;
+        type BlockHashCount = BlockHashCount;
+        type MaximumBlockWeight = MaximumBlockWeight;
+        type DbWeight = ();
+        type BlockExecutionWeight = ();
+        type ExtrinsicBaseWeight = ();
+        type MaximumExtrinsicWeight = MaximumBlockWeight;
+        type MaximumBlockLength = MaximumBlockLength;
+        type AvailableBlockRatio = AvailableBlockRatio;
+        type Version = ();
+        type PalletInfo = ();
+        type AccountData = pallet_balances::AccountData<u64>;
+        type OnNewAccount = ();
+        type OnKilledAccount = ();
+        type SystemWeightInfo = ();
+    }
+
+    parameter_types! {
+        pub const ExistentialDeposit: u64 = 1;
+    }
+
+    impl pallet_balances::Trait for Runtime {
+        type MaxLocks = ();
+        type Balance = u64;
+        type DustRemoval = ();
+        type Event = TestEvent;
+        type ExistentialDeposit = ExistentialDeposit;
+        type AccountStore = frame_system::Module<Runtime>;
+        type WeightInfo = ();
+    }
+
+    /// Only the first ten records of each account are written.
+    pub struct TestHistory;
+    impl RecordHistory<u64> for TestHistory {
+        fn contains(_account: &u64, seq: u64) -> bool {
+            seq < 10
+        }
+
+        #[cfg(feature = "runtime-benchmarks")]
+        fn note_record(_account: &u64, _seq: u64) {}
+    }
+
+    parameter_types! {
+        pub const MaxProjectLength: u32 = 16;
+        pub const MaxPurposeLength: u32 = 32;
+        pub const CertificateFeeBase: u64 = 10;
+        pub const CertificateFeePerByte: u64 = 1;
+    }
+
+    impl Trait for Runtime {
+        type Event = TestEvent;
+        type AssetId = u32;
+        type Balance = u64;
+        type RegistrarOrigin = frame_system::EnsureRoot<u64>;
+        type MaxProjectLength = MaxProjectLength;
+        type MaxPurposeLength = MaxPurposeLength;
+        type RecordHistory = TestHistory;
+        type Currency = pallet_balances::Module<Runtime>;
+        type CertificateFeeBase = CertificateFeeBase;
+        type CertificateFeePerByte = CertificateFeePerByte;
+        type WeightInfo = ();
+    }
+
+    type CarbonAssets = Module<Runtime>;
+    type System = frame_system::Module<Runtime>;
+    type RuntimeError = Error<Runtime>;
+
+    const OWNER: u64 = 1;
+    const ISSUER: u64 = 2;
+
+    pub(crate) fn new_test_ext() -> frame_support::sp_io::TestExternalities {
+        let mut storage = frame_system::GenesisConfig::default()
+            .build_storage::<Runtime>()
+            .unwrap();
+        pallet_balances::GenesisConfig::<Runtime> {
+            balances: vec![(4, 100), (5, 100)],
+        }
+        .assimilate_storage(&mut storage)
+        .unwrap();
+        GenesisConfig::<Runtime> {
+            classes: vec![(OWNER, b"VCS-1234".to_vec(), 2019, 2, vec![ISSUER])],
+        }
+        .assimilate_storage(&mut storage)
+        .unwrap();
+        storage.into()
+    }
+
+    /// The latest `n` carbon assets events, events are deposited since the first block.
+    fn last_events(n: usize) -> Vec<Event<Runtime>> {
+        let events: Vec<_> = System::events()
+            .into_iter()
+            .filter_map(|record| match record.event {
+                TestEvent::carbon_assets(event) => Some(event),
+                _ => None,
+            })
+            .collect();
+        events[events.len().saturating_sub(n)..].to_vec()
+    }
+
+    #[test]
+    fn test_genesis_classes() {
+        new_test_ext().execute_with(|| {
+            assert_eq!(
+                CarbonAssets::class(0),
+                Some(AssetClass {
+                    owner: OWNER,
+                    project: b"VCS-1234".to_vec(),
+                    vintage: 2019,
+                    supply: 0,
+                    min_balance: 2,
+                })
+            );
+            assert!(CarbonAssets::is_issuer(0, ISSUER));
+            assert_eq!(CarbonAssets::next_class_id(), 1);
+        })
+    }
+
+    #[test]
+    fn test_create_class() {
+        new_test_ext().execute_with(|| {
+            let project = b"VCS-1234".to_vec();
+            assert_noop!(
+                CarbonAssets::create_class(Origin::signed(OWNER), OWNER, project.clone(), 2020, 1),
+                DispatchError::BadOrigin
+            );
+            assert_noop!(
+                CarbonAssets::create_class(Origin::root(), OWNER, vec![0; 17], 2020, 1),
+                RuntimeError::ProjectTooLong
+            );
+            assert_noop!(
+                CarbonAssets::create_class(Origin::root(), OWNER, project.clone(), 2020, 0),
+                RuntimeError::ZeroAmount
+            );
+
+            System::set_block_number(1);
+            assert_ok!(CarbonAssets::create_class(Origin::root(), 3, project.clone(), 2020, 5));
+            assert_eq!(last_events(1), vec![RawEvent::ClassCreated(1, 3)]);
+            assert_eq!(
+                CarbonAssets::class(1).map(|info| (info.owner, info.vintage, info.min_balance)),
+                Some((3, 2020, 5))
+            );
+            assert_eq!(CarbonAssets::next_class_id(), 2);
+        })
+    }
+
+    #[test]
+    fn test_issuers() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            assert_noop!(
+                CarbonAssets::add_issuer(Origin::signed(ISSUER), 0, 3),
+                RuntimeError::NotOwner
+            );
+            assert_noop!(
+                CarbonAssets::add_issuer(Origin::signed(OWNER), 1, 3),
+                RuntimeError::UnknownClass
+            );
+            assert_ok!(CarbonAssets::add_issuer(Origin::signed(OWNER), 0, 3));
+            assert_eq!(last_events(1), vec![RawEvent::IssuerAdded(0, 3)]);
+            assert_ok!(CarbonAssets::mint(Origin::signed(3), 0, 4, 10));
+
+            assert_ok!(CarbonAssets::remove_issuer(Origin::signed(OWNER), 0, 3));
+            assert_eq!(last_events(1), vec![RawEvent::IssuerRemoved(0, 3)]);
+            assert_noop!(
+                CarbonAssets::mint(Origin::signed(3), 0, 4, 10),
+                RuntimeError::NotIssuer
+            );
+
+            assert_ok!(CarbonAssets::set_owner(Origin::signed(OWNER), 0, 3));
+            assert_eq!(last_events(1), vec![RawEvent::OwnerChanged(0, 3)]);
+            assert_noop!(
+                CarbonAssets::add_issuer(Origin::signed(OWNER), 0, 5),
+                RuntimeError::NotOwner
+            );
+            assert_ok!(CarbonAssets::add_issuer(Origin::signed(3), 0, 5));
+        })
+    }
+
+    #[test]
+    fn test_mint() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            assert_noop!(
+                CarbonAssets::mint(Origin::signed(OWNER), 0, 4, 10),
+                RuntimeError::NotIssuer
+            );
+            assert_noop!(
+                CarbonAssets::mint(Origin::signed(ISSUER), 0, 4, 0),
+                RuntimeError::ZeroAmount
+            );
+            assert_ok!(CarbonAssets::mint(Origin::signed(ISSUER), 0, 4, 10));
+            assert_ok!(CarbonAssets::mint(Origin::signed(ISSUER), 0, 5, 5));
+            assert_eq!(
+                last_events(2),
+                vec![RawEvent::Issued(0, 4, 10), RawEvent::Issued(0, 5, 5)]
+            );
+
+            assert_eq!(CarbonAssets::balance(0, 4), 10);
+            assert_eq!(CarbonAssets::balance(0, 5), 5);
+            assert_eq!(CarbonAssets::class(0).map(|info| info.supply), Some(15));
+            assert_noop!(
+                CarbonAssets::mint(Origin::signed(ISSUER), 0, 4, u64::max_value()),
+                RuntimeError::Overflow
+            );
+        })
+    }
+
+    #[test]
+    fn test_transfer() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            assert_ok!(CarbonAssets::mint(Origin::signed(ISSUER), 0, 4, 10));
+            assert_noop!(
+                CarbonAssets::transfer(Origin::signed(4), 0, 5, 11),
+                RuntimeError::InsufficientBalance
+            );
+            assert_noop!(
+                CarbonAssets::transfer(Origin::signed(4), 1, 5, 1),
+                RuntimeError::UnknownClass
+            );
+
+            assert_ok!(CarbonAssets::transfer(Origin::signed(4), 0, 5, 4));
+            assert_ok!(CarbonAssets::transfer(Origin::signed(4), 0, 4, 6));
+            assert_eq!(
+                last_events(2),
+                vec![RawEvent::Transferred(0, 4, 5, 4), RawEvent::Transferred(0, 4, 4, 6)]
+            );
+            assert_eq!(CarbonAssets::balance(0, 4), 6);
+            assert_eq!(CarbonAssets::balance(0, 5), 4);
+            assert_eq!(CarbonAssets::class(0).map(|info| info.supply), Some(10));
+        })
+    }
+
+    #[test]
+    fn test_min_balance() {
+        new_test_ext().execute_with(|| {
+            assert_noop!(
+                CarbonAssets::mint(Origin::signed(ISSUER), 0, 4, 1),
+                RuntimeError::BelowMinimum
+            );
+            assert_ok!(CarbonAssets::mint(Origin::signed(ISSUER), 0, 4, 10));
+
+            // dust can't be sent to a new holder nor left behind
+            assert_noop!(
+                CarbonAssets::transfer(Origin::signed(4), 0, 5, 1),
+                RuntimeError::BelowMinimum
+            );
+            assert_noop!(
+                CarbonAssets::transfer(Origin::signed(4), 0, 5, 9),
+                RuntimeError::BelowMinimum
+            );
+            assert_noop!(
+                CarbonAssets::retire(Origin::signed(4), 0, 9, 5, vec![], None),
+                RuntimeError::BelowMinimum
+            );
+
+            // emptied account leaves the storage
+            assert_ok!(CarbonAssets::transfer(Origin::signed(4), 0, 5, 10));
+            assert!(Balances::<Runtime>::iter_prefix(0).all(|(who, _)| who != 4));
+            assert_eq!(CarbonAssets::balance(0, 5), 10);
+
+            // holders may exchange any amount above the minimum
+            assert_ok!(CarbonAssets::mint(Origin::signed(ISSUER), 0, 4, 2));
+            assert_ok!(CarbonAssets::transfer(Origin::signed(5), 0, 4, 1));
+            assert_eq!(CarbonAssets::balance(0, 4), 3);
+        })
+    }
+
+    #[test]
+    fn test_retire() {
+        new_test_ext().execute_with(|| {
+            let purpose = b"2020 office emissions".to_vec();
+            assert_ok!(CarbonAssets::mint(Origin::signed(ISSUER), 0, 4, 10));
+            assert_noop!(
+                CarbonAssets::retire(Origin::signed(4), 0, 11, 5, purpose.clone(), None),
+                RuntimeError::InsufficientBalance
+            );
+            assert_noop!(
+                CarbonAssets::retire(Origin::signed(4), 0, 1, 5, vec![0; 33], None),
+                RuntimeError::PurposeTooLong
+            );
+            assert_noop!(
+                CarbonAssets::retire(Origin::signed(4), 1, 1, 5, purpose.clone(), None),
+                RuntimeError::UnknownClass
+            );
+            assert_noop!(
+                CarbonAssets::retire(Origin::signed(4), 0, 1, 5, purpose.clone(), Some((4, 10))),
+                RuntimeError::UnknownRecord
+            );
+            assert_ok!(CarbonAssets::mint(Origin::signed(ISSUER), 0, 6, 10));
+            assert_noop!(
+                CarbonAssets::retire(Origin::signed(6), 0, 10, 5, vec![], None),
+                RuntimeError::InsufficientFee
+            );
+            // fee can't reap the account
+            let _ = pallet_balances::Module::<Runtime>::make_free_balance_be(
+                &6,
+                CertificateFeeBase::get(),
+            );
+            assert_noop!(
+                CarbonAssets::retire(Origin::signed(6), 0, 10, 5, vec![], None),
+                RuntimeError::InsufficientFee
+            );
+
+            System::set_block_number(3);
+            assert_ok!(CarbonAssets::retire(
+                Origin::signed(4),
+                0,
+                4,
+                5,
+                purpose.clone(),
+                Some((4, 7))
+            ));
+            assert_ok!(CarbonAssets::retire(Origin::signed(4), 0, 1, 4, vec![], None));
+            assert_ok!(CarbonAssets::retire(Origin::signed(4), 0, 2, 5, vec![], None));
+            assert_eq!(
+                last_events(3),
+                vec![
+                    RawEvent::Retired(0, 4, 5, 4, 0),
+                    RawEvent::Retired(0, 4, 4, 1, 1),
+                    RawEvent::Retired(0, 4, 5, 2, 2),
+                ]
+            );
+
+            assert_eq!(CarbonAssets::balance(0, 4), 3);
+            assert_eq!(CarbonAssets::class(0).map(|info| info.supply), Some(13));
+            // certificate fees are burned
+            assert_eq!(
+                pallet_balances::Module::<Runtime>::free_balance(4),
+                100 - 3 * CertificateFeeBase::get() - purpose.len() as u64
+            );
+            assert_eq!(pallet_balances::Module::<Runtime>::reserved_balance(4), 0);
+            assert_eq!(
+                pallet_balances::Module::<Runtime>::total_issuance(),
+                200 - 3 * CertificateFeeBase::get() - purpose.len() as u64
+            );
+            let certificate = Certificate {
+                class: 0,
+                retired_by: 4,
+                beneficiary: 5,
+                amount: 4,
+                purpose,
+                datalog: Some((4, 7)),
+                block: 3,
+            };
+            assert_eq!(CarbonAssets::certificate(0), Some(certificate.clone()));
+            assert_eq!(CarbonAssets::next_certificate_id(), 3);
+
+            let mut certificates = CarbonAssets::certificates_of(&5);
+            certificates.sort_by_key(|(id, _)| *id);
+            assert_eq!(certificates.iter().map(|(id, _)| *id).collect::<Vec<_>>(), vec![0, 2]);
+            assert_eq!(certificates[0].1, certificate);
+            assert_eq!(CarbonAssets::certificates_of(&4).len(), 1);
+        })
+    }
+}
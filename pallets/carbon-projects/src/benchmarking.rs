@@ -0,0 +1,211 @@
+///////////////////////////////////////////////////////////////////////////////
+//
+//  Copyright 2018-2020 Airalab <research@aira.life>
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+///////////////////////////////////////////////////////////////////////////////
+//! Carbon projects pallet benchmarking.
+#![cfg(feature = "runtime-benchmarks")]
+
+use super::*;
+use frame_benchmarking::{account, benchmarks, whitelisted_caller};
+use frame_support::{
+    sp_runtime::traits::{Bounded, StaticLookup},
+    sp_std::vec,
+    traits::{Currency, EnsureOrigin, UnfilteredDispatchable},
+};
+use frame_system::RawOrigin;
+use pallet_identity::{Call as IdentityCall, IdentityInfo};
+
+/// Add `registrar` and let it judge identity of `who` as reasonable.
+fn judged<T: Trait>(registrar: &T::AccountId, who: &T::AccountId) -> Result<(), &'static str> {
+    let balance = Bounded::max_value();
+    <T as pallet_identity::Trait>::Currency::make_free_balance_be(who, balance);
+    IdentityCall::<T>::add_registrar(registrar.clone())
+        .dispatch_bypass_filter(<T as pallet_identity::Trait>::RegistrarOrigin::successful_origin())
+        .map_err(|e| e.error)?;
+    let reg_index = pallet_identity::Module::<T>::registrars().len() as RegistrarIndex - 1;
+    IdentityCall::<T>::set_identity(IdentityInfo::default())
+        .dispatch_bypass_filter(RawOrigin::Signed(who.clone()).into())
+        .map_err(|e| e.error)?;
+    IdentityCall::<T>::provide_judgement(
+        reg_index,
+        T::Lookup::unlookup(who.clone()),
+        Judgement::Reasonable,
+    )
+    .dispatch_bypass_filter(RawOrigin::Signed(registrar.clone()).into())
+    .map_err(|e| e.error)?;
+    Ok(())
+}
+
+/// Caller with balance enough for deposits.
+fn funded_caller<T: Trait>() -> T::AccountId {
+    let caller: T::AccountId = whitelisted_caller();
+    let balance = DepositOf::<T>::max_value() / 2u32.into();
+    <T as Trait>::Currency::make_free_balance_be(&caller, balance);
+    caller
+}
+
+/// Register project of `owner` and move it to `stage`.
+fn project<T: Trait>(owner: &T::AccountId, stage: Stage) -> ProjectId {
+    let id = NextProjectId::get();
+    let validator = match stage {
+        Stage::Draft => None,
+        _ => Some(account("validator", 0, 0)),
+    };
+    let info = Project { owner: owner.clone(), metadata: Default::default(), stage, validator };
+    Projects::<T>::insert(id, info);
+    NextProjectId::put(id + 1);
+    id
+}
+
+/// Submit report of monitored project owned by `owner` with `status`.
+fn report<T: Trait>(owner: &T::AccountId, status: ReportStatus) -> ReportId {
+    let id = NextReportId::get();
+    let verifier = match status {
+        ReportStatus::Submitted => None,
+        _ => Some(account("verifier", 0, 0)),
+    };
+    let info = Report {
+        project: project::<T>(owner, Stage::Monitoring),
+        vintage: 2020,
+        amount: T::ClassMinBalance::get().max(100u32.into()),
+        metadata: Default::default(),
+        records: vec![],
+        status,
+        verifier,
+    };
+    Reports::<T>::insert(id, info);
+    NextReportId::put(id + 1);
+    id
+}
+
+benchmarks! {
+    _ { }
+
+    assign_role {
+        let caller: T::AccountId = whitelisted_caller();
+        let who: T::AccountId = account("validator", 0, 0);
+        judged::<T>(&caller, &who)?;
+    }: _(RawOrigin::Signed(caller), 0, who.clone(), Role::Validator)
+    verify {
+        assert_eq!(Roles::<T>::get(&who, Role::Validator), Some(0));
+    }
+
+    revoke_role {
+        let caller: T::AccountId = whitelisted_caller();
+        let who: T::AccountId = account("validator", 0, 0);
+        judged::<T>(&caller, &who)?;
+        Roles::<T>::insert(&who, Role::Validator, 0);
+    }: _(RawOrigin::Signed(caller), who.clone(), Role::Validator)
+    verify {
+        assert!(!Roles::<T>::contains_key(&who, Role::Validator));
+    }
+
+    register {
+        let caller = funded_caller::<T>();
+    }: _(RawOrigin::Signed(caller), Default::default())
+    verify {
+        assert!(Projects::<T>::contains_key(0));
+    }
+
+    remove_project {
+        let caller = funded_caller::<T>();
+        Module::<T>::register(RawOrigin::Signed(caller.clone()).into(), Default::default())?;
+    }: _(RawOrigin::Signed(caller), 0)
+    verify {
+        assert!(!Projects::<T>::contains_key(0));
+    }
+
+    validate {
+        let caller: T::AccountId = whitelisted_caller();
+        Roles::<T>::insert(&caller, Role::Validator, 0);
+        let owner: T::AccountId = account("owner", 0, 0);
+        let id = project::<T>(&owner, Stage::Draft);
+    }: _(RawOrigin::Signed(caller), id)
+    verify {
+        assert_eq!(Projects::<T>::get(id).map(|info| info.stage), Some(Stage::Validated));
+    }
+
+    start_monitoring {
+        let caller: T::AccountId = whitelisted_caller();
+        let id = project::<T>(&caller, Stage::Validated);
+    }: _(RawOrigin::Signed(caller), id)
+    verify {
+        assert_eq!(Projects::<T>::get(id).map(|info| info.stage), Some(Stage::Monitoring));
+    }
+
+    submit_report {
+        let r in 0 .. T::MaxReportRecords::get();
+        let caller = funded_caller::<T>();
+        let id = project::<T>(&caller, Stage::Monitoring);
+        let records = (0..r as u64).map(|seq| (caller.clone(), seq)).collect::<Vec<_>>();
+        if r > 0 {
+            T::RecordHistory::note_record(&caller, r as u64 - 1);
+        }
+        let amount = T::ClassMinBalance::get().max(100u32.into());
+    }: _(RawOrigin::Signed(caller), id, 2020, amount, Default::default(), records)
+    verify {
+        assert!(Reports::<T>::contains_key(0));
+    }
+
+    remove_report {
+        let caller = funded_caller::<T>();
+        let id = report::<T>(&caller, ReportStatus::Submitted);
+    }: _(RawOrigin::Signed(caller), id)
+    verify {
+        assert!(!Reports::<T>::contains_key(id));
+    }
+
+    verify_report {
+        let caller: T::AccountId = whitelisted_caller();
+        Roles::<T>::insert(&caller, Role::Verifier, 0);
+        let owner: T::AccountId = account("owner", 0, 0);
+        let id = report::<T>(&owner, ReportStatus::Submitted);
+    }: _(RawOrigin::Signed(caller), id)
+    verify {
+        assert_eq!(Reports::<T>::get(id).map(|info| info.status), Some(ReportStatus::Verified));
+    }
+
+    issue {
+        let caller: T::AccountId = whitelisted_caller();
+        let id = report::<T>(&caller, ReportStatus::Verified);
+    }: _(RawOrigin::Signed(caller), id)
+    verify {
+        assert_eq!(Reports::<T>::get(id).map(|info| info.status), Some(ReportStatus::Issued));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{new_test_ext, Runtime};
+    use frame_support::assert_ok;
+
+    #[test]
+    fn test_benchmarks() {
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_assign_role::<Runtime>());
+            assert_ok!(test_benchmark_revoke_role::<Runtime>());
+            assert_ok!(test_benchmark_register::<Runtime>());
+            assert_ok!(test_benchmark_remove_project::<Runtime>());
+            assert_ok!(test_benchmark_validate::<Runtime>());
+            assert_ok!(test_benchmark_start_monitoring::<Runtime>());
+            assert_ok!(test_benchmark_submit_report::<Runtime>());
+            assert_ok!(test_benchmark_remove_report::<Runtime>());
+            assert_ok!(test_benchmark_verify_report::<Runtime>());
+            assert_ok!(test_benchmark_issue::<Runtime>());
+        });
+    }
+}
@@ -0,0 +1,99 @@
+///////////////////////////////////////////////////////////////////////////////
+//
+//  Copyright 2018-2020 Airalab <research@aira.life>
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+///////////////////////////////////////////////////////////////////////////////
+//! Weights for pallet_carbon_projects
+//!
+//! These are estimates derived from storage access of every call, not benchmark results.
+//! Replace them with output of `benchmarking.rs` on reference hardware:
+//!
+//! ipci benchmark --chain dev --execution wasm --wasm-execution compiled \
+//!     --pallet pallet_carbon_projects --extrinsic '*' --steps 50 --repeat 20
+//!
+//! with the node built using `--features runtime-benchmarks`.
+
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::weights::{constants::RocksDbWeight as DbWeight, Weight};
+
+pub trait WeightInfo {
+    fn assign_role() -> Weight;
+    fn revoke_role() -> Weight;
+    fn register() -> Weight;
+    fn remove_project() -> Weight;
+    fn validate() -> Weight;
+    fn start_monitoring() -> Weight;
+    fn submit_report(r: u32) -> Weight;
+    fn remove_report() -> Weight;
+    fn verify_report() -> Weight;
+    fn issue() -> Weight;
+}
+
+impl WeightInfo for () {
+    fn assign_role() -> Weight {
+        (38_240_000 as Weight)
+            .saturating_add(DbWeight::get().reads(2 as Weight))
+            .saturating_add(DbWeight::get().writes(1 as Weight))
+    }
+    fn revoke_role() -> Weight {
+        (30_710_000 as Weight)
+            .saturating_add(DbWeight::get().reads(2 as Weight))
+            .saturating_add(DbWeight::get().writes(1 as Weight))
+    }
+    fn register() -> Weight {
+        (45_000_000 as Weight)
+            .saturating_add(DbWeight::get().reads(2 as Weight))
+            .saturating_add(DbWeight::get().writes(4 as Weight))
+    }
+    fn remove_project() -> Weight {
+        (40_000_000 as Weight)
+            .saturating_add(DbWeight::get().reads(3 as Weight))
+            .saturating_add(DbWeight::get().writes(3 as Weight))
+    }
+    fn validate() -> Weight {
+        (29_860_000 as Weight)
+            .saturating_add(DbWeight::get().reads(2 as Weight))
+            .saturating_add(DbWeight::get().writes(1 as Weight))
+    }
+    fn start_monitoring() -> Weight {
+        (25_120_000 as Weight)
+            .saturating_add(DbWeight::get().reads(1 as Weight))
+            .saturating_add(DbWeight::get().writes(1 as Weight))
+    }
+    fn submit_report(r: u32) -> Weight {
+        (35_930_000 as Weight)
+            .saturating_add((3_270_000 as Weight).saturating_mul(r as Weight))
+            .saturating_add(DbWeight::get().reads(3 as Weight))
+            .saturating_add(DbWeight::get().reads((1 as Weight).saturating_mul(r as Weight)))
+            .saturating_add(DbWeight::get().writes(4 as Weight))
+    }
+    fn remove_report() -> Weight {
+        (45_000_000 as Weight)
+            .saturating_add(DbWeight::get().reads(4 as Weight))
+            .saturating_add(DbWeight::get().writes(3 as Weight))
+    }
+    fn verify_report() -> Weight {
+        (32_480_000 as Weight)
+            .saturating_add(DbWeight::get().reads(3 as Weight))
+            .saturating_add(DbWeight::get().writes(1 as Weight))
+    }
+    fn issue() -> Weight {
+        (64_570_000 as Weight)
+            .saturating_add(DbWeight::get().reads(5 as Weight))
+            .saturating_add(DbWeight::get().writes(6 as Weight))
+    }
+}
@@ -0,0 +1,1081 @@
+///////////////////////////////////////////////////////////////////////////////
+//
+//  Copyright 2018-2020 Airalab <research@aira.life>
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+///////////////////////////////////////////////////////////////////////////////
+//! Emission reduction project registry runtime module.
+//!
+//! Projects pass the Measurement/Reporting/Verification lifecycle: registered project
+//! is validated, then its owner submits monitoring reports referring to datalog records.
+//! Asset units are issued only for reports approved by a verifier. Validator and verifier
+//! roles are assigned by `pallet_identity` registrars to accounts they have judged.
+//! Projects and reports are stored for a deposit, returned when a project or report is
+//! removed before review; reviewed ones stay in the registry together with their deposit.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use frame_support::{
+    codec::{Decode, Encode},
+    decl_error, decl_event, decl_module, decl_storage, ensure,
+    sp_runtime::{
+        traits::{AccountIdConversion, Saturating, Zero},
+        DispatchResult, ModuleId, RuntimeDebug,
+    },
+    sp_std::prelude::*,
+    traits::{Currency, Get, ReservableCurrency},
+};
+use frame_system::ensure_signed;
+use pallet_identity::{Judgement, RegistrarIndex};
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+
+pub use default_weight::WeightInfo;
+pub use pallet_carbon_assets::RecordHistory;
+mod benchmarking;
+mod default_weight;
+
+/// Account owning asset classes of registered projects.
+const PALLET_ID: ModuleId = ModuleId(*b"ipci/prj");
+
+/// Project identifier.
+pub type ProjectId = u64;
+/// Monitoring report identifier.
+pub type ReportId = u64;
+/// Balance of carbon asset units.
+pub type BalanceOf<T> = <T as pallet_carbon_assets::Trait>::Balance;
+/// Carbon asset class identifier.
+pub type AssetIdOf<T> = <T as pallet_carbon_assets::Trait>::AssetId;
+/// Balance of deposit currency.
+pub type DepositOf<T> =
+    <<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::Balance;
+
+/// Stage of project lifecycle.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum Stage {
+    /// Registered, waiting for validation.
+    Draft,
+    /// Project design validated.
+    Validated,
+    /// Emission reductions are monitored and reported.
+    Monitoring,
+}
+
+/// Status of monitoring report.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum ReportStatus {
+    /// Submitted, waiting for verification.
+    Submitted,
+    /// Reported emission reductions verified.
+    Verified,
+    /// Asset units of verified emission reductions issued.
+    Issued,
+}
+
+/// Role assigned by identity registrar.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum Role {
+    /// Validates project design.
+    Validator,
+    /// Verifies monitoring reports.
+    Verifier,
+}
+
+/// Emission reduction project.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct Project<AccountId, Hash> {
+    /// Project owner, receives issued units.
+    pub owner: AccountId,
+    /// Hash of project design document.
+    pub metadata: Hash,
+    /// Lifecycle stage.
+    pub stage: Stage,
+    /// Account validated project design.
+    pub validator: Option<AccountId>,
+}
+
+/// Report of emission reductions achieved in monitoring period.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct Report<AccountId, Hash, Balance> {
+    /// Reporting project.
+    pub project: ProjectId,
+    /// Year emission reductions were achieved in.
+    pub vintage: u16,
+    /// Emission reductions in asset units.
+    pub amount: Balance,
+    /// Hash of report document.
+    pub metadata: Hash,
+    /// Datalog records with measurements: [datalog owner, sequence number].
+    pub records: Vec<(AccountId, u64)>,
+    /// Verification status.
+    pub status: ReportStatus,
+    /// Account verified the report.
+    pub verifier: Option<AccountId>,
+}
+
+/// Monitoring report of runtime.
+pub type ReportOf<T> = Report<
+    <T as frame_system::Trait>::AccountId,
+    <T as frame_system::Trait>::Hash,
+    BalanceOf<T>,
+>;
+
+/// Project registry module main trait.
+pub trait Trait:
+    frame_system::Trait + pallet_identity::Trait + pallet_carbon_assets::Trait
+{
+    /// The overarching event type.
+    type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
+    /// Maximum number of datalog records referred to by one report.
+    type MaxReportRecords: Get<u32>;
+    /// Minimal balance of asset classes created for project vintages.
+    type ClassMinBalance: Get<BalanceOf<Self>>;
+    /// Currency of project and report deposits.
+    type Currency: ReservableCurrency<Self::AccountId>;
+    /// Deposit reserved for every registered project.
+    type ProjectDeposit: Get<DepositOf<Self>>;
+    /// Deposit reserved for every monitoring report.
+    type ReportDepositBase: Get<DepositOf<Self>>;
+    /// Deposit reserved for every datalog record referred to by report.
+    type ReportDepositPerRecord: Get<DepositOf<Self>>;
+    /// extrinsic weights
+    type WeightInfo: WeightInfo;
+}
+
+decl_error! {
+    pub enum Error for Module<T: Trait> {
+        /// Project doesn't exist.
+        UnknownProject,
+        /// Report doesn't exist.
+        UnknownReport,
+        /// Sender is not project owner.
+        NotOwner,
+        /// Sender is not registrar with given index.
+        NotRegistrar,
+        /// Account identity is not judged as reasonable or known good by registrar.
+        NotJudged,
+        /// Sender has no role required.
+        MissingRole,
+        /// Role was assigned by another registrar.
+        NotAssigner,
+        /// Action is not allowed at current project stage.
+        WrongStage,
+        /// Action is not allowed at current report status.
+        WrongStatus,
+        /// Project owner can't validate or verify own project.
+        SelfReview,
+        /// Report refers to more datalog records than allowed.
+        TooManyRecords,
+        /// Report refers to datalog record that was never written.
+        UnknownRecord,
+        /// Reported amount should be positive.
+        ZeroAmount,
+        /// Reported amount is below minimal balance of asset classes.
+        BelowMinimum,
+    }
+}
+
+decl_event! {
+    pub enum Event<T>
+    where AccountId = <T as frame_system::Trait>::AccountId,
+          AssetId = AssetIdOf<T>,
+          Balance = BalanceOf<T>,
+    {
+        /// Role assigned by registrar: [account, role, registrar index].
+        RoleAssigned(AccountId, Role, RegistrarIndex),
+        /// Role revoked: [account, role].
+        RoleRevoked(AccountId, Role),
+        /// Project registered: [project, owner].
+        ProjectRegistered(ProjectId, AccountId),
+        /// Draft project removed by its owner.
+        ProjectRemoved(ProjectId),
+        /// Project design validated: [project, validator].
+        ProjectValidated(ProjectId, AccountId),
+        /// Project monitoring started.
+        MonitoringStarted(ProjectId),
+        /// Monitoring report submitted: [report, project].
+        ReportSubmitted(ReportId, ProjectId),
+        /// Submitted report removed by project owner.
+        ReportRemoved(ReportId),
+        /// Monitoring report verified: [report, verifier].
+        ReportVerified(ReportId, AccountId),
+        /// Asset units issued for report: [report, class, amount].
+        ReportIssued(ReportId, AssetId, Balance),
+    }
+}
+
+decl_storage! {
+    trait Store for Module<T: Trait> as CarbonProjects {
+        /// Roles of accounts with index of registrar assigned them.
+        Roles get(fn role): double_map hasher(blake2_128_concat) T::AccountId,
+                                 hasher(twox_64_concat) Role => Option<RegistrarIndex>;
+        /// Identifier of the next project.
+        NextProjectId get(fn next_project_id): ProjectId;
+        /// Registered projects.
+        Projects get(fn project): map hasher(twox_64_concat)
+                                 ProjectId => Option<Project<T::AccountId, T::Hash>>;
+        /// Identifier of the next report.
+        NextReportId get(fn next_report_id): ReportId;
+        /// Monitoring reports.
+        Reports get(fn report): map hasher(twox_64_concat) ReportId => Option<ReportOf<T>>;
+        /// Deposit reserved from project owner.
+        ProjectDeposits get(fn project_deposit): map hasher(twox_64_concat)
+                                 ProjectId => DepositOf<T>;
+        /// Deposit reserved from project owner for monitoring report.
+        ReportDeposits get(fn report_deposit): map hasher(twox_64_concat)
+                                 ReportId => DepositOf<T>;
+        /// Asset classes of project vintages.
+        ProjectClasses get(fn project_class): double_map hasher(twox_64_concat) ProjectId,
+                                 hasher(twox_64_concat) u16 => Option<AssetIdOf<T>>;
+    }
+}
+
+decl_module! {
+    pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+        type Error = Error<T>;
+
+        fn deposit_event() = default;
+
+        /// Assign `role` to account judged by registrar with index `reg_index`.
+        #[weight = <T as Trait>::WeightInfo::assign_role()]
+        fn assign_role(origin, #[compact] reg_index: RegistrarIndex, who: T::AccountId, role: Role) {
+            let sender = ensure_signed(origin)?;
+            ensure!(Self::is_registrar(&sender, reg_index), Error::<T>::NotRegistrar);
+            ensure!(Self::is_judged(&who, reg_index), Error::<T>::NotJudged);
+
+            Roles::<T>::insert(&who, role, reg_index);
+            Self::deposit_event(RawEvent::RoleAssigned(who, role, reg_index));
+        }
+
+        /// Revoke `role` assigned by sender registrar.
+        #[weight = <T as Trait>::WeightInfo::revoke_role()]
+        fn revoke_role(origin, who: T::AccountId, role: Role) {
+            let sender = ensure_signed(origin)?;
+            let reg_index = Roles::<T>::get(&who, role).ok_or(Error::<T>::MissingRole)?;
+            ensure!(Self::is_registrar(&sender, reg_index), Error::<T>::NotAssigner);
+
+            Roles::<T>::remove(&who, role);
+            Self::deposit_event(RawEvent::RoleRevoked(who, role));
+        }
+
+        /// Register project with design document hash `metadata`.
+        ///
+        /// Project deposit is reserved from sender.
+        #[weight = <T as Trait>::WeightInfo::register()]
+        fn register(origin, metadata: T::Hash) {
+            let sender = ensure_signed(origin)?;
+            let deposit = T::ProjectDeposit::get();
+            <T as Trait>::Currency::reserve(&sender, deposit)?;
+
+            let id = NextProjectId::get();
+            let project = Project {
+                owner: sender.clone(),
+                metadata,
+                stage: Stage::Draft,
+                validator: None,
+            };
+            Projects::<T>::insert(id, project);
+            ProjectDeposits::<T>::insert(id, deposit);
+            NextProjectId::put(id.saturating_add(1));
+            Self::deposit_event(RawEvent::ProjectRegistered(id, sender));
+        }
+
+        /// Remove draft project of sender, project deposit is returned.
+        #[weight = <T as Trait>::WeightInfo::remove_project()]
+        fn remove_project(origin, project: ProjectId) {
+            let sender = ensure_signed(origin)?;
+            let info = Projects::<T>::get(project).ok_or(Error::<T>::UnknownProject)?;
+            ensure!(info.owner == sender, Error::<T>::NotOwner);
+            ensure!(info.stage == Stage::Draft, Error::<T>::WrongStage);
+
+            Projects::<T>::remove(project);
+            let deposit = ProjectDeposits::<T>::take(project);
+            <T as Trait>::Currency::unreserve(&sender, deposit);
+            Self::deposit_event(RawEvent::ProjectRemoved(project));
+        }
+
+        /// Approve design of draft project.
+        #[weight = <T as Trait>::WeightInfo::validate()]
+        fn validate(origin, project: ProjectId) {
+            let sender = ensure_signed(origin)?;
+            ensure!(Roles::<T>::contains_key(&sender, Role::Validator), Error::<T>::MissingRole);
+
+            Projects::<T>::try_mutate(project, |info| -> DispatchResult {
+                let info = info.as_mut().ok_or(Error::<T>::UnknownProject)?;
+                ensure!(info.owner != sender, Error::<T>::SelfReview);
+                ensure!(info.stage == Stage::Draft, Error::<T>::WrongStage);
+                info.stage = Stage::Validated;
+                info.validator = Some(sender.clone());
+                Ok(())
+            })?;
+            Self::deposit_event(RawEvent::ProjectValidated(project, sender));
+        }
+
+        /// Start monitoring of validated project.
+        #[weight = <T as Trait>::WeightInfo::start_monitoring()]
+        fn start_monitoring(origin, project: ProjectId) {
+            let sender = ensure_signed(origin)?;
+
+            Projects::<T>::try_mutate(project, |info| -> DispatchResult {
+                let info = info.as_mut().ok_or(Error::<T>::UnknownProject)?;
+                ensure!(info.owner == sender, Error::<T>::NotOwner);
+                ensure!(info.stage == Stage::Validated, Error::<T>::WrongStage);
+                info.stage = Stage::Monitoring;
+                Ok(())
+            })?;
+            Self::deposit_event(RawEvent::MonitoringStarted(project));
+        }
+
+        /// Report `amount` of emission reductions achieved in `vintage` year.
+        ///
+        /// Report refers to datalog `records` with measurements of the period. Amount should
+        /// be at least minimal balance of asset classes. Report deposit is reserved from sender.
+        #[weight = <T as Trait>::WeightInfo::submit_report(records.len() as u32)]
+        fn submit_report(
+            origin,
+            project: ProjectId,
+            vintage: u16,
+            #[compact] amount: BalanceOf<T>,
+            metadata: T::Hash,
+            records: Vec<(T::AccountId, u64)>,
+        ) {
+            let sender = ensure_signed(origin)?;
+            ensure!(
+                records.len() <= T::MaxReportRecords::get() as usize,
+                Error::<T>::TooManyRecords
+            );
+            ensure!(!amount.is_zero(), Error::<T>::ZeroAmount);
+            ensure!(amount >= T::ClassMinBalance::get(), Error::<T>::BelowMinimum);
+            let info = Projects::<T>::get(project).ok_or(Error::<T>::UnknownProject)?;
+            ensure!(info.owner == sender, Error::<T>::NotOwner);
+            ensure!(info.stage == Stage::Monitoring, Error::<T>::WrongStage);
+            ensure!(
+                records.iter().all(|(account, seq)| T::RecordHistory::contains(account, *seq)),
+                Error::<T>::UnknownRecord
+            );
+            let deposit = T::ReportDepositPerRecord::get()
+                .saturating_mul((records.len() as u32).into())
+                .saturating_add(T::ReportDepositBase::get());
+            <T as Trait>::Currency::reserve(&sender, deposit)?;
+
+            let id = NextReportId::get();
+            let report = Report {
+                project,
+                vintage,
+                amount,
+                metadata,
+                records,
+                status: ReportStatus::Submitted,
+                verifier: None,
+            };
+            Reports::<T>::insert(id, report);
+            ReportDeposits::<T>::insert(id, deposit);
+            NextReportId::put(id.saturating_add(1));
+            Self::deposit_event(RawEvent::ReportSubmitted(id, project));
+        }
+
+        /// Remove submitted report of sender`s project, report deposit is returned.
+        #[weight = <T as Trait>::WeightInfo::remove_report()]
+        fn remove_report(origin, report: ReportId) {
+            let sender = ensure_signed(origin)?;
+            let info = Reports::<T>::get(report).ok_or(Error::<T>::UnknownReport)?;
+            let project = Projects::<T>::get(info.project).ok_or(Error::<T>::UnknownProject)?;
+            ensure!(project.owner == sender, Error::<T>::NotOwner);
+            ensure!(info.status == ReportStatus::Submitted, Error::<T>::WrongStatus);
+
+            Reports::<T>::remove(report);
+            let deposit = ReportDeposits::<T>::take(report);
+            <T as Trait>::Currency::unreserve(&sender, deposit);
+            Self::deposit_event(RawEvent::ReportRemoved(report));
+        }
+
+        /// Approve submitted monitoring report.
+        #[weight = <T as Trait>::WeightInfo::verify_report()]
+        fn verify_report(origin, report: ReportId) {
+            let sender = ensure_signed(origin)?;
+            ensure!(Roles::<T>::contains_key(&sender, Role::Verifier), Error::<T>::MissingRole);
+
+            Reports::<T>::try_mutate(report, |info| -> DispatchResult {
+                let info = info.as_mut().ok_or(Error::<T>::UnknownReport)?;
+                let project = Projects::<T>::get(info.project).ok_or(Error::<T>::UnknownProject)?;
+                ensure!(project.owner != sender, Error::<T>::SelfReview);
+                ensure!(info.status == ReportStatus::Submitted, Error::<T>::WrongStatus);
+                info.status = ReportStatus::Verified;
+                info.verifier = Some(sender.clone());
+                Ok(())
+            })?;
+            Self::deposit_event(RawEvent::ReportVerified(report, sender));
+        }
+
+        /// Issue asset units of verified report to project owner.
+        ///
+        /// Units belong to asset class of project vintage created on the first issue.
+        #[weight = <T as Trait>::WeightInfo::issue()]
+        fn issue(origin, report: ReportId) {
+            let sender = ensure_signed(origin)?;
+            let mut info = Reports::<T>::get(report).ok_or(Error::<T>::UnknownReport)?;
+            let project = Projects::<T>::get(info.project).ok_or(Error::<T>::UnknownProject)?;
+            ensure!(project.owner == sender, Error::<T>::NotOwner);
+            ensure!(info.status == ReportStatus::Verified, Error::<T>::WrongStatus);
+
+            let class = match ProjectClasses::<T>::get(info.project, info.vintage) {
+                Some(class) => class,
+                None => {
+                    let class = pallet_carbon_assets::Module::<T>::create(
+                        Self::account_id(),
+                        info.project.encode(),
+                        info.vintage,
+                        T::ClassMinBalance::get(),
+                    )?;
+                    ProjectClasses::<T>::insert(info.project, info.vintage, class);
+                    class
+                }
+            };
+            pallet_carbon_assets::Module::<T>::issue(class, &sender, info.amount)?;
+
+            info.status = ReportStatus::Issued;
+            let amount = info.amount;
+            Reports::<T>::insert(report, info);
+            Self::deposit_event(RawEvent::ReportIssued(report, class, amount));
+        }
+    }
+}
+
+impl<T: Trait> Module<T> {
+    /// Account owning asset classes of projects, nobody holds its key.
+    pub fn account_id() -> T::AccountId {
+        PALLET_ID.into_account()
+    }
+
+    fn is_registrar(who: &T::AccountId, reg_index: RegistrarIndex) -> bool {
+        pallet_identity::Module::<T>::registrars()
+            .get(reg_index as usize)
+            .and_then(Option::as_ref)
+            .map_or(false, |registrar| &registrar.account == who)
+    }
+
+    fn is_judged(who: &T::AccountId, reg_index: RegistrarIndex) -> bool {
+        pallet_identity::Module::<T>::identity(who).map_or(false, |registration| {
+            registration.judgements.iter().any(|(index, judgement)| {
+                *index == reg_index
+                    && matches!(judgement, Judgement::Reasonable | Judgement::KnownGood)
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use frame_support::{
+        assert_noop, assert_ok, impl_outer_event, impl_outer_origin, parameter_types,
+        traits::UnfilteredDispatchable,
+        sp_runtime::{
+            testing::Header,
+            traits::{BlakeTwo256, IdentityLookup},
+            Perbill,
+        },
+        weights::Weight,
+    };
+    use pallet_identity::{Call as IdentityCall, IdentityInfo};
+    use sp_core::H256;
+
+    impl_outer_origin! {
+        pub enum Origin for Runtime {}
+    }
+
+    mod carbon_projects {
+        pub use crate::Event;
+    }
+
+    impl_outer_event! {
+        pub enum TestEvent for Runtime {
+            frame_system<T>,
+            pallet_balances<T>,
+            pallet_identity<T>,
+            pallet_carbon_assets<T>,
+            carbon_projects<T>,
+        }
+    }
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    pub struct Runtime;
+
+    parameter_types! {
+        pub const BlockHashCount: u64 = 250;
+        pub const MaximumBlockWeight: Weight = 1024;
+        pub const MaximumBlockLength: u32 = 2 * 1024;
+        pub const AvailableBlockRatio: Perbill = Perbill::one();
+    }
+
+    impl frame_system::Trait for Runtime {
+        type BaseCallFilter = ();
+        type Origin = Origin;
+        type Index = u64;
+        type BlockNumber = u64;
+        type Call = ();
+        type Hash = H256;
+        type Hashing = BlakeTwo256;
+        type AccountId = u64;
+        type Lookup = IdentityLookup<Self::AccountId>;
+        type Header = Header;
+        type Event = TestEvent;
+        type BlockHashCount = BlockHashCount;
+        type MaximumBlockWeight = MaximumBlockWeight;
+        type DbWeight = ();
+        type BlockExecutionWeight = ();
+        type ExtrinsicBaseWeight = ();
+        type MaximumExtrinsicWeight = MaximumBlockWeight;
+        type MaximumBlockLength = MaximumBlockLength;
+        type AvailableBlockRatio = AvailableBlockRatio;
+        type Version = ();
+        type PalletInfo = ();
+        type AccountData = pallet_balances::AccountData<u64>;
+        type OnNewAccount = ();
+        type OnKilledAccount = ();
+        type SystemWeightInfo = ();
+    }
+
+    parameter_types! {
+        pub const ExistentialDeposit: u64 = 1;
+    }
+
+    impl pallet_balances::Trait for Runtime {
+        type MaxLocks = ();
+        type Balance = u64;
+        type DustRemoval = ();
+        type Event = TestEvent;
+        type ExistentialDeposit = ExistentialDeposit;
+        type AccountStore = frame_system::Module<Runtime>;
+        type WeightInfo = ();
+    }
+
+    parameter_types! {
+        pub const BasicDeposit: u64 = 10;
+        pub const FieldDeposit: u64 = 1;
+        pub const SubAccountDeposit: u64 = 1;
+        pub const MaxSubAccounts: u32 = 2;
+        pub const MaxAdditionalFields: u32 = 2;
+        pub const MaxRegistrars: u32 = 4;
+    }
+
+    impl pallet_identity::Trait for Runtime {
+        type Event = TestEvent;
+        type Currency = pallet_balances::Module<Runtime>;
+        type BasicDeposit = BasicDeposit;
+        type FieldDeposit = FieldDeposit;
+        type SubAccountDeposit = SubAccountDeposit;
+        type MaxSubAccounts = MaxSubAccounts;
+        type MaxAdditionalFields = MaxAdditionalFields;
+        type MaxRegistrars = MaxRegistrars;
+        type Slashed = ();
+        type ForceOrigin = frame_system::EnsureRoot<u64>;
+        type RegistrarOrigin = frame_system::EnsureRoot<u64>;
+        type WeightInfo = ();
+    }
+
+    parameter_types! {
+        pub const MaxProjectLength: u32 = 16;
+        pub const MaxPurposeLength: u32 = 32;
+        pub const CertificateFeeBase: u64 = 10;
+        pub const CertificateFeePerByte: u64 = 1;
+    }
+
+    impl pallet_carbon_assets::Trait for Runtime {
+        type Event = TestEvent;
+        type AssetId = u32;
+        type Balance = u64;
+        type RegistrarOrigin = frame_system::EnsureRoot<u64>;
+        type MaxProjectLength = MaxProjectLength;
+        type MaxPurposeLength = MaxPurposeLength;
+        type RecordHistory = TestHistory;
+        type Currency = pallet_balances::Module<Runtime>;
+        type CertificateFeeBase = CertificateFeeBase;
+        type CertificateFeePerByte = CertificateFeePerByte;
+        type WeightInfo = ();
+    }
+
+    /// Only the first ten records of each account are written.
+    pub struct TestHistory;
+    impl RecordHistory<u64> for TestHistory {
+        fn contains(_account: &u64, seq: u64) -> bool {
+            seq < 10
+        }
+
+        #[cfg(feature = "runtime-benchmarks")]
+        fn note_record(_account: &u64, _seq: u64) {}
+    }
+
+    parameter_types! {
+        pub const MaxReportRecords: u32 = 3;
+        pub const ClassMinBalance: u64 = 10;
+        pub const ProjectDeposit: u64 = PROJECT_DEPOSIT;
+        pub const ReportDepositBase: u64 = 5;
+        pub const ReportDepositPerRecord: u64 = 1;
+    }
+
+    impl Trait for Runtime {
+        type Event = TestEvent;
+        type MaxReportRecords = MaxReportRecords;
+        type ClassMinBalance = ClassMinBalance;
+        type Currency = pallet_balances::Module<Runtime>;
+        type ProjectDeposit = ProjectDeposit;
+        type ReportDepositBase = ReportDepositBase;
+        type ReportDepositPerRecord = ReportDepositPerRecord;
+        type WeightInfo = ();
+    }
+
+    type Identity = pallet_identity::Module<Runtime>;
+    type CarbonAssets = pallet_carbon_assets::Module<Runtime>;
+    type CarbonProjects = Module<Runtime>;
+    type System = frame_system::Module<Runtime>;
+    type Balances = pallet_balances::Module<Runtime>;
+    type RuntimeError = Error<Runtime>;
+
+    const REGISTRAR: u64 = 1;
+    const OWNER: u64 = 2;
+    const VALIDATOR: u64 = 3;
+    const VERIFIER: u64 = 4;
+    const PROJECT_DEPOSIT: u64 = 10;
+
+    pub(crate) fn new_test_ext() -> frame_support::sp_io::TestExternalities {
+        let mut storage = frame_system::GenesisConfig::default()
+            .build_storage::<Runtime>()
+            .unwrap();
+        pallet_balances::GenesisConfig::<Runtime> {
+            balances: vec![(REGISTRAR, 100), (OWNER, 100), (VALIDATOR, 100), (VERIFIER, 100)],
+        }
+        .assimilate_storage(&mut storage)
+        .unwrap();
+        storage.into()
+    }
+
+    /// The latest `n` carbon projects and assets events.
+    fn last_events(n: usize) -> Vec<TestEvent> {
+        let events: Vec<_> = System::events()
+            .into_iter()
+            .map(|record| record.event)
+            .filter(|event| {
+                matches!(
+                    event,
+                    TestEvent::carbon_projects(_) | TestEvent::pallet_carbon_assets(_)
+                )
+            })
+            .collect();
+        events[events.len().saturating_sub(n)..].to_vec()
+    }
+
+    /// Judge identity of `who` by registrar 0.
+    fn judge(who: u64, judgement: Judgement<u64>) {
+        if Identity::registrars().is_empty() {
+            let call = IdentityCall::<Runtime>::add_registrar(REGISTRAR);
+            assert_ok!(call.dispatch_bypass_filter(Origin::root()));
+        }
+        let call = IdentityCall::<Runtime>::set_identity(IdentityInfo::default());
+        assert_ok!(call.dispatch_bypass_filter(Origin::signed(who)));
+        let call = IdentityCall::<Runtime>::provide_judgement(0, who, judgement);
+        assert_ok!(call.dispatch_bypass_filter(Origin::signed(REGISTRAR)));
+    }
+
+    /// Assign validator and verifier roles, register project and start its monitoring.
+    fn monitored_project() -> ProjectId {
+        judge(VALIDATOR, Judgement::Reasonable);
+        judge(VERIFIER, Judgement::KnownGood);
+        assert_ok!(CarbonProjects::assign_role(
+            Origin::signed(REGISTRAR),
+            0,
+            VALIDATOR,
+            Role::Validator
+        ));
+        assert_ok!(CarbonProjects::assign_role(
+            Origin::signed(REGISTRAR),
+            0,
+            VERIFIER,
+            Role::Verifier
+        ));
+
+        let project = CarbonProjects::next_project_id();
+        assert_ok!(CarbonProjects::register(Origin::signed(OWNER), H256::repeat_byte(1)));
+        assert_ok!(CarbonProjects::validate(Origin::signed(VALIDATOR), project));
+        assert_ok!(CarbonProjects::start_monitoring(Origin::signed(OWNER), project));
+        project
+    }
+
+    #[test]
+    fn test_assign_role() {
+        new_test_ext().execute_with(|| {
+            assert_noop!(
+                CarbonProjects::assign_role(Origin::signed(REGISTRAR), 0, VALIDATOR, Role::Validator),
+                RuntimeError::NotRegistrar
+            );
+
+            judge(VALIDATOR, Judgement::LowQuality);
+            assert_noop!(
+                CarbonProjects::assign_role(Origin::signed(OWNER), 0, VALIDATOR, Role::Validator),
+                RuntimeError::NotRegistrar
+            );
+            assert_noop!(
+                CarbonProjects::assign_role(Origin::signed(REGISTRAR), 0, VALIDATOR, Role::Validator),
+                RuntimeError::NotJudged
+            );
+
+            judge(VALIDATOR, Judgement::Reasonable);
+            assert_ok!(CarbonProjects::assign_role(
+                Origin::signed(REGISTRAR),
+                0,
+                VALIDATOR,
+                Role::Validator
+            ));
+            assert_eq!(CarbonProjects::role(VALIDATOR, Role::Validator), Some(0));
+            assert_eq!(CarbonProjects::role(VALIDATOR, Role::Verifier), None);
+
+            assert_noop!(
+                CarbonProjects::revoke_role(Origin::signed(OWNER), VALIDATOR, Role::Validator),
+                RuntimeError::NotAssigner
+            );
+            assert_noop!(
+                CarbonProjects::revoke_role(Origin::signed(REGISTRAR), VALIDATOR, Role::Verifier),
+                RuntimeError::MissingRole
+            );
+            assert_ok!(CarbonProjects::revoke_role(
+                Origin::signed(REGISTRAR),
+                VALIDATOR,
+                Role::Validator
+            ));
+            assert_eq!(CarbonProjects::role(VALIDATOR, Role::Validator), None);
+        })
+    }
+
+    #[test]
+    fn test_project_lifecycle() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            judge(VALIDATOR, Judgement::Reasonable);
+            assert_ok!(CarbonProjects::assign_role(
+                Origin::signed(REGISTRAR),
+                0,
+                VALIDATOR,
+                Role::Validator
+            ));
+
+            let metadata = H256::repeat_byte(1);
+            assert_ok!(CarbonProjects::register(Origin::signed(OWNER), metadata));
+            assert_eq!(
+                CarbonProjects::project(0),
+                Some(Project { owner: OWNER, metadata, stage: Stage::Draft, validator: None })
+            );
+            assert_eq!(last_events(1), vec![RawEvent::ProjectRegistered(0, OWNER).into()]);
+            assert_eq!(Balances::reserved_balance(OWNER), PROJECT_DEPOSIT);
+
+            assert_noop!(
+                CarbonProjects::start_monitoring(Origin::signed(OWNER), 0),
+                RuntimeError::WrongStage
+            );
+            assert_noop!(
+                CarbonProjects::validate(Origin::signed(VERIFIER), 0),
+                RuntimeError::MissingRole
+            );
+            assert_noop!(
+                CarbonProjects::validate(Origin::signed(VALIDATOR), 1),
+                RuntimeError::UnknownProject
+            );
+            assert_ok!(CarbonProjects::validate(Origin::signed(VALIDATOR), 0));
+            assert_eq!(last_events(1), vec![RawEvent::ProjectValidated(0, VALIDATOR).into()]);
+            assert_noop!(
+                CarbonProjects::validate(Origin::signed(VALIDATOR), 0),
+                RuntimeError::WrongStage
+            );
+
+            assert_noop!(
+                CarbonProjects::start_monitoring(Origin::signed(VALIDATOR), 0),
+                RuntimeError::NotOwner
+            );
+            assert_ok!(CarbonProjects::start_monitoring(Origin::signed(OWNER), 0));
+            assert_eq!(last_events(1), vec![RawEvent::MonitoringStarted(0).into()]);
+            assert_eq!(
+                CarbonProjects::project(0),
+                Some(Project {
+                    owner: OWNER,
+                    metadata,
+                    stage: Stage::Monitoring,
+                    validator: Some(VALIDATOR),
+                })
+            );
+
+            // Validator can't approve own project
+            assert_ok!(CarbonProjects::register(Origin::signed(VALIDATOR), metadata));
+            assert_noop!(
+                CarbonProjects::validate(Origin::signed(VALIDATOR), 1),
+                RuntimeError::SelfReview
+            );
+        })
+    }
+
+    #[test]
+    fn test_submit_report() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            assert_ok!(CarbonProjects::register(Origin::signed(OWNER), H256::repeat_byte(1)));
+            let metadata = H256::repeat_byte(2);
+            assert_noop!(
+                CarbonProjects::submit_report(Origin::signed(OWNER), 0, 2020, 100, metadata, vec![]),
+                RuntimeError::WrongStage
+            );
+
+            let project = monitored_project();
+            assert_noop!(
+                CarbonProjects::submit_report(
+                    Origin::signed(VALIDATOR),
+                    project,
+                    2020,
+                    100,
+                    metadata,
+                    vec![]
+                ),
+                RuntimeError::NotOwner
+            );
+            assert_noop!(
+                CarbonProjects::submit_report(
+                    Origin::signed(OWNER),
+                    project,
+                    2020,
+                    100,
+                    metadata,
+                    vec![(5, 0), (5, 1), (5, 2), (5, 3)]
+                ),
+                RuntimeError::TooManyRecords
+            );
+            assert_noop!(
+                CarbonProjects::submit_report(
+                    Origin::signed(OWNER),
+                    project,
+                    2020,
+                    100,
+                    metadata,
+                    vec![(5, 0), (5, 10)]
+                ),
+                RuntimeError::UnknownRecord
+            );
+            assert_noop!(
+                CarbonProjects::submit_report(
+                    Origin::signed(OWNER),
+                    project,
+                    2020,
+                    0,
+                    metadata,
+                    vec![]
+                ),
+                RuntimeError::ZeroAmount
+            );
+            assert_noop!(
+                CarbonProjects::submit_report(
+                    Origin::signed(OWNER),
+                    project,
+                    2020,
+                    9,
+                    metadata,
+                    vec![]
+                ),
+                RuntimeError::BelowMinimum
+            );
+
+            let records = vec![(5, 0), (5, 9)];
+            let reserved = Balances::reserved_balance(OWNER);
+            assert_ok!(CarbonProjects::submit_report(
+                Origin::signed(OWNER),
+                project,
+                2020,
+                100,
+                metadata,
+                records.clone()
+            ));
+            assert_eq!(last_events(1), vec![RawEvent::ReportSubmitted(0, project).into()]);
+            // base deposit and one per record
+            assert_eq!(Balances::reserved_balance(OWNER), reserved + 5 + 2);
+            assert_eq!(
+                CarbonProjects::report(0),
+                Some(Report {
+                    project,
+                    vintage: 2020,
+                    amount: 100,
+                    metadata,
+                    records,
+                    status: ReportStatus::Submitted,
+                    verifier: None,
+                })
+            );
+            assert_eq!(CarbonProjects::next_report_id(), 1);
+        })
+    }
+
+    #[test]
+    fn test_verify_and_issue() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let project = monitored_project();
+            let metadata = H256::repeat_byte(2);
+            for &(vintage, amount) in &[(2020, 100), (2020, 50), (2021, 70)] {
+                assert_ok!(CarbonProjects::submit_report(
+                    Origin::signed(OWNER),
+                    project,
+                    vintage,
+                    amount,
+                    metadata,
+                    vec![]
+                ));
+            }
+
+            assert_noop!(
+                CarbonProjects::issue(Origin::signed(OWNER), 0),
+                RuntimeError::WrongStatus
+            );
+            assert_noop!(
+                CarbonProjects::verify_report(Origin::signed(VALIDATOR), 0),
+                RuntimeError::MissingRole
+            );
+            assert_noop!(
+                CarbonProjects::verify_report(Origin::signed(VERIFIER), 3),
+                RuntimeError::UnknownReport
+            );
+            for report in 0..3 {
+                assert_ok!(CarbonProjects::verify_report(Origin::signed(VERIFIER), report));
+            }
+            assert_eq!(last_events(1), vec![RawEvent::ReportVerified(2, VERIFIER).into()]);
+            assert_noop!(
+                CarbonProjects::verify_report(Origin::signed(VERIFIER), 0),
+                RuntimeError::WrongStatus
+            );
+            assert_eq!(
+                CarbonProjects::report(0).map(|info| (info.status, info.verifier)),
+                Some((ReportStatus::Verified, Some(VERIFIER)))
+            );
+
+            assert_noop!(
+                CarbonProjects::issue(Origin::signed(VERIFIER), 0),
+                RuntimeError::NotOwner
+            );
+            for report in 0..3 {
+                assert_ok!(CarbonProjects::issue(Origin::signed(OWNER), report));
+            }
+            // asset class of every vintage is created on its first issue
+            let account = CarbonProjects::account_id();
+            assert_eq!(
+                last_events(8),
+                vec![
+                    pallet_carbon_assets::RawEvent::ClassCreated(0, account).into(),
+                    pallet_carbon_assets::RawEvent::Issued(0, OWNER, 100).into(),
+                    RawEvent::ReportIssued(0, 0, 100).into(),
+                    pallet_carbon_assets::RawEvent::Issued(0, OWNER, 50).into(),
+                    RawEvent::ReportIssued(1, 0, 50).into(),
+                    pallet_carbon_assets::RawEvent::ClassCreated(1, account).into(),
+                    pallet_carbon_assets::RawEvent::Issued(1, OWNER, 70).into(),
+                    RawEvent::ReportIssued(2, 1, 70).into(),
+                ]
+            );
+            assert_noop!(
+                CarbonProjects::issue(Origin::signed(OWNER), 0),
+                RuntimeError::WrongStatus
+            );
+
+            // Reports of one vintage share asset class owned by module account
+            assert_eq!(CarbonProjects::project_class(project, 2020), Some(0));
+            assert_eq!(CarbonProjects::project_class(project, 2021), Some(1));
+            assert_eq!(
+                CarbonAssets::class(0).map(|info| (info.owner, info.project, info.supply)),
+                Some((CarbonProjects::account_id(), project.encode(), 150))
+            );
+            assert_eq!(CarbonAssets::balance(0, OWNER), 150);
+            assert_eq!(CarbonAssets::balance(1, OWNER), 70);
+            assert_eq!(
+                CarbonProjects::report(2).map(|info| info.status),
+                Some(ReportStatus::Issued)
+            );
+        })
+    }
+
+    #[test]
+    fn test_remove() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let metadata = H256::repeat_byte(1);
+            assert_ok!(CarbonProjects::register(Origin::signed(OWNER), metadata));
+            assert_noop!(
+                CarbonProjects::remove_project(Origin::signed(VALIDATOR), 0),
+                RuntimeError::NotOwner
+            );
+            assert_ok!(CarbonProjects::remove_project(Origin::signed(OWNER), 0));
+            assert_eq!(CarbonProjects::project(0), None);
+            assert_eq!(last_events(1), vec![RawEvent::ProjectRemoved(0).into()]);
+            assert_eq!(Balances::reserved_balance(OWNER), 0);
+
+            // reviewed projects and reports stay in the registry
+            let project = monitored_project();
+            assert_noop!(
+                CarbonProjects::remove_project(Origin::signed(OWNER), project),
+                RuntimeError::WrongStage
+            );
+            for _ in 0..2 {
+                assert_ok!(CarbonProjects::submit_report(
+                    Origin::signed(OWNER),
+                    project,
+                    2020,
+                    100,
+                    metadata,
+                    vec![(5, 0)]
+                ));
+            }
+            assert_eq!(Balances::reserved_balance(OWNER), PROJECT_DEPOSIT + 2 * 6);
+            assert_ok!(CarbonProjects::verify_report(Origin::signed(VERIFIER), 0));
+            assert_noop!(
+                CarbonProjects::remove_report(Origin::signed(OWNER), 0),
+                RuntimeError::WrongStatus
+            );
+            assert_noop!(
+                CarbonProjects::remove_report(Origin::signed(VERIFIER), 1),
+                RuntimeError::NotOwner
+            );
+            assert_ok!(CarbonProjects::remove_report(Origin::signed(OWNER), 1));
+            assert_eq!(CarbonProjects::report(1), None);
+            assert_eq!(last_events(1), vec![RawEvent::ReportRemoved(1).into()]);
+            assert_eq!(Balances::reserved_balance(OWNER), PROJECT_DEPOSIT + 6);
+        })
+    }
+
+    #[test]
+    fn test_verifier_self_review() {
+        new_test_ext().execute_with(|| {
+            monitored_project();
+            assert_ok!(CarbonProjects::register(Origin::signed(VERIFIER), H256::repeat_byte(1)));
+            assert_ok!(CarbonProjects::validate(Origin::signed(VALIDATOR), 1));
+            assert_ok!(CarbonProjects::start_monitoring(Origin::signed(VERIFIER), 1));
+            assert_ok!(CarbonProjects::submit_report(
+                Origin::signed(VERIFIER),
+                1,
+                2020,
+                100,
+                H256::repeat_byte(2),
+                vec![]
+            ));
+            assert_noop!(
+                CarbonProjects::verify_report(Origin::signed(VERIFIER), 0),
+                RuntimeError::SelfReview
+            );
+        })
+    }
+}
@@ -0,0 +1,88 @@
+///////////////////////////////////////////////////////////////////////////////
+//
+//  Copyright 2018-2020 Airalab <research@aira.life>
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+///////////////////////////////////////////////////////////////////////////////
+//! Runtime API definition required by datalog RPC extensions.
+//!
+//! This API should be imported and implemented by the runtime,
+//! of a node that wants to use the datalog RPC extension.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+pub use pallet_robonomics_datalog::{Entry, MerkleProof, RecordChange, RingBufferIndex};
+use sp_std::prelude::*;
+
+sp_api::decl_runtime_apis! {
+    pub trait DatalogApi<AccountId, Moment, Record> where
+        AccountId: Codec,
+        Moment: Codec,
+        Record: Codec,
+    {
+        /// Time tagged records of account, oldest first.
+        fn records(account: AccountId) -> Vec<(Moment, Record)>;
+        /// Records of account with timestamp in `[from, to]` range.
+        fn records_range(account: AccountId, from: Moment, to: Moment) -> Vec<(Moment, Record)>;
+        /// Last `count` records of account.
+        fn records_last(account: AccountId, count: u64) -> Vec<(Moment, Record)>;
+        /// Page of at most `limit` records starting from `cursor` and the cursor of the next page.
+        fn records_page(
+            account: AccountId,
+            cursor: u64,
+            limit: u64,
+        ) -> (Vec<(Moment, Record)>, Option<u64>);
+        /// Ring buffer start/end pointers of account.
+        fn index(account: AccountId) -> RingBufferIndex;
+        /// Ring buffer window size of account.
+        fn window_size(account: AccountId) -> u64;
+        /// Records sent into inbox of account with their senders, oldest first.
+        fn inbox(account: AccountId) -> Vec<(AccountId, Moment, Record)>;
+        /// Records of account with their sequence numbers and attesting devices, oldest first.
+        fn entries(account: AccountId) -> Vec<Entry<AccountId, Moment, Record>>;
+    }
+
+    pub trait DatalogMmrApi<AccountId, Hash> where
+        AccountId: Codec,
+        Hash: Codec,
+    {
+        /// Merkle mountain range root of account`s records.
+        fn mmr_root(account: AccountId) -> Hash;
+        /// Number of account`s records committed into merkle mountain range.
+        fn mmr_leaves(account: AccountId) -> u64;
+        /// Merkle mountain range peaks of account`s records, highest first.
+        ///
+        /// Inclusion proofs are built off-chain from indexed nodes, see `mmr::proof_from_nodes`.
+        fn mmr_peaks(account: AccountId) -> Vec<Hash>;
+    }
+
+    pub trait DatalogBlockApi<AccountId, BlockNumber> where
+        AccountId: Codec,
+        BlockNumber: Codec,
+    {
+        /// Block the `seq`-th record of account was written in, if it is still stored and known.
+        fn record_block(account: AccountId, seq: u64) -> Option<BlockNumber>;
+    }
+
+    pub trait DatalogEventsApi<AccountId, Moment, Record> where
+        AccountId: Codec,
+        Moment: Codec,
+        Record: Codec,
+    {
+        /// Datalog changes made in the block the call is made at.
+        ///
+        /// Events are decoded by the runtime of that block, so it works across upgrades.
+        fn record_changes() -> Vec<RecordChange<AccountId, Moment, Record>>;
+    }
+}
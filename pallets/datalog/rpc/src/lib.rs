@@ -0,0 +1,241 @@
+///////////////////////////////////////////////////////////////////////////////
+//
+//  Copyright 2018-2020 Airalab <research@aira.life>
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+///////////////////////////////////////////////////////////////////////////////
+//! RPC interface for the datalog module.
+
+use codec::Codec;
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+pub use pallet_robonomics_datalog_rpc_runtime_api::DatalogApi as DatalogRuntimeApi;
+pub use pallet_robonomics_datalog_rpc_runtime_api::DatalogEventsApi as DatalogEventsRuntimeApi;
+pub use pallet_robonomics_datalog_rpc_runtime_api::DatalogMmrApi as DatalogMmrRuntimeApi;
+use pallet_robonomics_datalog_rpc_runtime_api::{Entry, RingBufferIndex};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+use std::sync::Arc;
+
+/// Datalog RPC methods.
+#[rpc]
+pub trait DatalogApi<BlockHash, AccountId, Moment, Record> {
+    /// Time tagged records of account, oldest first.
+    #[rpc(name = "datalog_records")]
+    fn records(&self, account: AccountId, at: Option<BlockHash>) -> Result<Vec<(Moment, Record)>>;
+
+    /// Records of account with timestamp in `[from, to]` range.
+    #[rpc(name = "datalog_recordsRange")]
+    fn records_range(
+        &self,
+        account: AccountId,
+        from: Moment,
+        to: Moment,
+        at: Option<BlockHash>,
+    ) -> Result<Vec<(Moment, Record)>>;
+
+    /// Last `count` records of account.
+    #[rpc(name = "datalog_recordsLast")]
+    fn records_last(
+        &self,
+        account: AccountId,
+        count: u64,
+        at: Option<BlockHash>,
+    ) -> Result<Vec<(Moment, Record)>>;
+
+    /// Page of at most `limit` records starting from `cursor` and the cursor of the next page.
+    #[rpc(name = "datalog_recordsPage")]
+    fn records_page(
+        &self,
+        account: AccountId,
+        cursor: u64,
+        limit: u64,
+        at: Option<BlockHash>,
+    ) -> Result<(Vec<(Moment, Record)>, Option<u64>)>;
+
+    /// Ring buffer start/end pointers of account.
+    #[rpc(name = "datalog_index")]
+    fn index(&self, account: AccountId, at: Option<BlockHash>) -> Result<RingBufferIndex>;
+
+    /// Ring buffer window size of account.
+    #[rpc(name = "datalog_windowSize")]
+    fn window_size(&self, account: AccountId, at: Option<BlockHash>) -> Result<u64>;
+
+    /// Records sent into inbox of account with their senders, oldest first.
+    #[rpc(name = "datalog_inbox")]
+    fn inbox(
+        &self,
+        account: AccountId,
+        at: Option<BlockHash>,
+    ) -> Result<Vec<(AccountId, Moment, Record)>>;
+
+    /// Records of account with their sequence numbers and attesting devices, oldest first.
+    #[rpc(name = "datalog_entries")]
+    fn entries(
+        &self,
+        account: AccountId,
+        at: Option<BlockHash>,
+    ) -> Result<Vec<Entry<AccountId, Moment, Record>>>;
+}
+
+/// A struct that implements the [`DatalogApi`].
+pub struct Datalog<C, B> {
+    client: Arc<C>,
+    _marker: std::marker::PhantomData<B>,
+}
+
+impl<C, B> Datalog<C, B> {
+    /// Create new `Datalog` with the given reference to the client.
+    pub fn new(client: Arc<C>) -> Self {
+        Self {
+            client,
+            _marker: Default::default(),
+        }
+    }
+}
+
+/// Error type of this RPC api.
+pub enum Error {
+    /// The call to runtime failed.
+    RuntimeError,
+}
+
+impl From<Error> for i64 {
+    fn from(e: Error) -> i64 {
+        match e {
+            Error::RuntimeError => 1,
+        }
+    }
+}
+
+fn runtime_error(message: &str, e: impl std::fmt::Debug) -> RpcError {
+    RpcError {
+        code: ErrorCode::ServerError(Error::RuntimeError.into()),
+        message: message.into(),
+        data: Some(format!("{:?}", e).into()),
+    }
+}
+
+impl<C, Block, AccountId, Moment, Record>
+    DatalogApi<<Block as BlockT>::Hash, AccountId, Moment, Record> for Datalog<C, Block>
+where
+    Block: BlockT,
+    C: 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block>,
+    C::Api: DatalogRuntimeApi<Block, AccountId, Moment, Record>,
+    AccountId: Codec,
+    Moment: Codec,
+    Record: Codec,
+{
+    fn records(
+        &self,
+        account: AccountId,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Vec<(Moment, Record)>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+        api.records(&at, account)
+            .map_err(|e| runtime_error("Unable to query datalog records.", e))
+    }
+
+    fn records_range(
+        &self,
+        account: AccountId,
+        from: Moment,
+        to: Moment,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Vec<(Moment, Record)>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+        api.records_range(&at, account, from, to)
+            .map_err(|e| runtime_error("Unable to query datalog records.", e))
+    }
+
+    fn records_last(
+        &self,
+        account: AccountId,
+        count: u64,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Vec<(Moment, Record)>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+        api.records_last(&at, account, count)
+            .map_err(|e| runtime_error("Unable to query datalog records.", e))
+    }
+
+    fn records_page(
+        &self,
+        account: AccountId,
+        cursor: u64,
+        limit: u64,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<(Vec<(Moment, Record)>, Option<u64>)> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+        api.records_page(&at, account, cursor, limit)
+            .map_err(|e| runtime_error("Unable to query datalog records.", e))
+    }
+
+    fn index(
+        &self,
+        account: AccountId,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<RingBufferIndex> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+        api.index(&at, account)
+            .map_err(|e| runtime_error("Unable to query datalog index.", e))
+    }
+
+    fn window_size(
+        &self,
+        account: AccountId,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<u64> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+        api.window_size(&at, account)
+            .map_err(|e| runtime_error("Unable to query datalog window size.", e))
+    }
+
+    fn inbox(
+        &self,
+        account: AccountId,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Vec<(AccountId, Moment, Record)>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+        api.inbox(&at, account)
+            .map_err(|e| runtime_error("Unable to query datalog inbox.", e))
+    }
+
+    fn entries(
+        &self,
+        account: AccountId,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Vec<Entry<AccountId, Moment, Record>>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+        api.entries(&at, account)
+            .map_err(|e| runtime_error("Unable to query datalog entries.", e))
+    }
+}
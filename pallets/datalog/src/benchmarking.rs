@@ -0,0 +1,351 @@
+///////////////////////////////////////////////////////////////////////////////
+//
+//  Copyright 2018-2020 Airalab <research@aira.life>
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+///////////////////////////////////////////////////////////////////////////////
+//! Datalog pallet benchmarking.
+#![cfg(feature = "runtime-benchmarks")]
+
+use super::*;
+use frame_benchmarking::{account, benchmarks, whitelisted_caller};
+use frame_support::{sp_runtime::traits::Bounded, sp_std::vec};
+use frame_system::RawOrigin;
+
+/// Upper bound of record encoding overhead on top of its payload.
+const RECORD_OVERHEAD: usize = 16;
+
+fn funded_caller<T: Trait>() -> T::AccountId {
+    let caller: T::AccountId = whitelisted_caller();
+    T::Currency::make_free_balance_be(&caller, BalanceOf::<T>::max_value() / 2u32.into());
+    caller
+}
+
+fn record_of<T: Trait>(size: u32) -> T::Record {
+    T::Record::from(vec![0x42; size as usize])
+}
+
+/// Fill datalog of account with `n` records.
+fn fill<T: Trait>(account: &T::AccountId, n: u32) -> Result<(), &'static str> {
+    for _ in 0..n {
+        Module::<T>::record(RawOrigin::Signed(account.clone()).into(), record_of::<T>(32))?;
+    }
+    Ok(())
+}
+
+/// Register new device key to account, returns account of the device.
+fn register<T: Trait>(owner: &T::AccountId) -> Result<T::AccountId, &'static str> {
+    let device = T::DeviceKeys::generate();
+    let genesis_hash = frame_system::Module::<T>::block_hash(T::BlockNumber::zero());
+    let signature = T::DeviceKeys::sign(&device, &registration_payload(owner, &genesis_hash));
+    Module::<T>::register_device(
+        RawOrigin::Signed(owner.clone()).into(),
+        device.clone(),
+        signature,
+    )?;
+    Ok(device)
+}
+
+/// Send `n` records from `sender` into inbox of `recipient`.
+fn fill_inbox<T: Trait>(
+    sender: &T::AccountId,
+    recipient: &T::AccountId,
+    n: u32,
+) -> Result<(), &'static str> {
+    for _ in 0..n {
+        Module::<T>::send(
+            RawOrigin::Signed(sender.clone()).into(),
+            recipient.clone(),
+            record_of::<T>(32),
+        )?;
+    }
+    Ok(())
+}
+
+benchmarks! {
+    _ { }
+
+    // worst case: ring buffer is full and the oldest record is dropped
+    record {
+        let s in 1 .. (T::MaximumMessageSize::get() - RECORD_OVERHEAD) as u32;
+        let caller = funded_caller::<T>();
+        fill::<T>(&caller, T::WindowSize::get().saturating_sub(1) as u32)?;
+    }: _(RawOrigin::Signed(caller.clone()), record_of::<T>(s))
+    verify {
+        assert_eq!(
+            DatalogIndex::<T>::get(&caller).len(T::WindowSize::get()),
+            T::WindowSize::get() - 1,
+        );
+    }
+
+    record_batch {
+        let n in 1 .. T::MaxBatchLength::get();
+        let s in 1 .. T::MaxBatchLength::get()
+            * (T::MaximumMessageSize::get() - RECORD_OVERHEAD) as u32;
+        let caller = funded_caller::<T>();
+        fill::<T>(&caller, T::WindowSize::get().saturating_sub(1) as u32)?;
+        // `s` bytes of batch are spread evenly, records are trimmed to maximal size
+        let max_size = (T::MaximumMessageSize::get() - RECORD_OVERHEAD) as u32;
+        let records: Vec<_> = (0..n)
+            .map(|i| record_of::<T>((s / n + (i < s % n) as u32).min(max_size)))
+            .collect();
+    }: _(RawOrigin::Signed(caller.clone()), records)
+    verify {
+        assert_eq!(
+            DatalogIndex::<T>::get(&caller).len(T::WindowSize::get()),
+            T::WindowSize::get() - 1,
+        );
+    }
+
+    erase {
+        let n in 1 .. T::MaximumWindowSize::get().saturating_sub(1) as u32;
+        let caller = funded_caller::<T>();
+        DatalogWindow::<T>::insert(&caller, T::MaximumWindowSize::get());
+        fill::<T>(&caller, n)?;
+    }: _(RawOrigin::Signed(caller.clone()))
+    verify {
+        assert!(DatalogIndex::<T>::get(&caller).is_empty());
+    }
+
+    // every stored record is moved into the new window
+    set_window_size {
+        let n in 1 .. T::MaximumWindowSize::get().saturating_sub(1) as u32;
+        let caller = funded_caller::<T>();
+        DatalogWindow::<T>::insert(&caller, n as u64 + 1);
+        fill::<T>(&caller, n)?;
+    }: _(RawOrigin::Signed(caller.clone()), T::MaximumWindowSize::get())
+    verify {
+        assert_eq!(
+            DatalogIndex::<T>::get(&caller).len(T::MaximumWindowSize::get()),
+            n as u64,
+        );
+    }
+
+    record_for {
+        let s in 1 .. (T::MaximumMessageSize::get() - RECORD_OVERHEAD) as u32;
+        let owner = funded_caller::<T>();
+        let writer: T::AccountId = account("writer", 0, 0);
+        fill::<T>(&owner, T::WindowSize::get().saturating_sub(1) as u32)?;
+        Module::<T>::add_writer(
+            RawOrigin::Signed(owner.clone()).into(),
+            writer.clone(),
+            None,
+            Zero::zero(),
+        )?;
+    }: _(RawOrigin::Signed(writer), owner.clone(), record_of::<T>(s))
+    verify {
+        assert_eq!(
+            DatalogIndex::<T>::get(&owner).len(T::WindowSize::get()),
+            T::WindowSize::get() - 1,
+        );
+    }
+
+    add_writer {
+        let owner = funded_caller::<T>();
+        let writer: T::AccountId = account("writer", 0, 0);
+    }: _(RawOrigin::Signed(owner.clone()), writer.clone(), None, Zero::zero())
+    verify {
+        assert!(Writers::<T>::contains_key(&owner, &writer));
+    }
+
+    remove_writer {
+        let owner = funded_caller::<T>();
+        let writer: T::AccountId = account("writer", 0, 0);
+        Module::<T>::add_writer(
+            RawOrigin::Signed(owner.clone()).into(),
+            writer.clone(),
+            None,
+            Zero::zero(),
+        )?;
+    }: _(RawOrigin::Signed(owner.clone()), writer.clone())
+    verify {
+        assert!(!Writers::<T>::contains_key(&owner, &writer));
+    }
+
+    // worst case: inbox is full and the oldest record is dropped
+    send {
+        let s in 1 .. (T::MaximumMessageSize::get() - RECORD_OVERHEAD) as u32;
+        let sender = funded_caller::<T>();
+        let recipient: T::AccountId = account("recipient", 0, 0);
+        InboxSenders::<T>::insert(&recipient, &sender, true);
+        fill_inbox::<T>(&sender, &recipient, T::WindowSize::get().saturating_sub(1) as u32)?;
+    }: _(RawOrigin::Signed(sender), recipient.clone(), record_of::<T>(s))
+    verify {
+        assert_eq!(
+            InboxIndex::<T>::get(&recipient).len(T::WindowSize::get()),
+            T::WindowSize::get() - 1,
+        );
+    }
+
+    allow_sender {
+        let recipient = funded_caller::<T>();
+        let sender: T::AccountId = account("sender", 0, 0);
+    }: _(RawOrigin::Signed(recipient.clone()), sender.clone())
+    verify {
+        assert!(InboxSenders::<T>::get(&recipient, &sender));
+    }
+
+    disallow_sender {
+        let recipient = funded_caller::<T>();
+        let sender: T::AccountId = account("sender", 0, 0);
+        Module::<T>::allow_sender(RawOrigin::Signed(recipient.clone()).into(), sender.clone())?;
+    }: _(RawOrigin::Signed(recipient.clone()), sender.clone())
+    verify {
+        assert!(!InboxSenders::<T>::get(&recipient, &sender));
+    }
+
+    clear_inbox {
+        let n in 1 .. T::WindowSize::get().saturating_sub(1) as u32;
+        let sender = funded_caller::<T>();
+        let recipient: T::AccountId = account("recipient", 0, 0);
+        InboxSenders::<T>::insert(&recipient, &sender, true);
+        fill_inbox::<T>(&sender, &recipient, n)?;
+    }: _(RawOrigin::Signed(recipient.clone()))
+    verify {
+        assert!(InboxIndex::<T>::get(&recipient).is_empty());
+    }
+
+    // worst case: record in the middle of the full window
+    erase_item {
+        let n in 1 .. (T::MaximumWindowSize::get() / 2).saturating_sub(1) as u32;
+        let caller = funded_caller::<T>();
+        DatalogWindow::<T>::insert(&caller, 2 * n as u64 + 2);
+        fill::<T>(&caller, 2 * n + 1)?;
+    }: _(RawOrigin::Signed(caller.clone()), n as u64)
+    verify {
+        assert_eq!(
+            DatalogIndex::<T>::get(&caller).len(2 * n as u64 + 2),
+            2 * n as u64,
+        );
+    }
+
+    erase_older_than {
+        let n in 1 .. T::MaximumWindowSize::get().saturating_sub(1) as u32;
+        let caller = funded_caller::<T>();
+        DatalogWindow::<T>::insert(&caller, T::MaximumWindowSize::get());
+        fill::<T>(&caller, n)?;
+        let moment = T::Time::now().saturating_add(1u32.into());
+    }: _(RawOrigin::Signed(caller.clone()), moment)
+    verify {
+        assert!(DatalogIndex::<T>::get(&caller).is_empty());
+    }
+
+    set_retention {
+        let caller = funded_caller::<T>();
+        let period: MomentOf<T> = 1u32.into();
+    }: _(RawOrigin::Signed(caller.clone()), Some(period))
+    verify {
+        assert_eq!(DatalogRetention::<T>::get(&caller), Some(period));
+    }
+
+    force_erase {
+        let n in 1 .. T::MaximumWindowSize::get().saturating_sub(1) as u32;
+        let a in 0 .. T::MaximumWindowSize::get().saturating_sub(1) as u32;
+        let caller = funded_caller::<T>();
+        DatalogWindow::<T>::insert(&caller, T::MaximumWindowSize::get());
+        fill::<T>(&caller, n)?;
+    }: {
+        Module::<T>::force_erase(T::ForceOrigin::successful_origin(), caller.clone(), a)?;
+    }
+    verify {
+        assert!(DatalogIndex::<T>::get(&caller).is_empty());
+    }
+
+    freeze {
+        let account: T::AccountId = account("account", 0, 0);
+    }: {
+        Module::<T>::freeze(T::ForceOrigin::successful_origin(), account.clone())?;
+    }
+    verify {
+        assert!(Frozen::<T>::get(&account));
+    }
+
+    thaw {
+        let account: T::AccountId = account("account", 0, 0);
+        Frozen::<T>::insert(&account, true);
+    }: {
+        Module::<T>::thaw(T::ForceOrigin::successful_origin(), account.clone())?;
+    }
+    verify {
+        assert!(!Frozen::<T>::get(&account));
+    }
+
+    // worst case: ring buffer is full and the oldest record is dropped
+    record_attested {
+        let s in 1 .. (T::MaximumMessageSize::get() - RECORD_OVERHEAD) as u32;
+        let caller = funded_caller::<T>();
+        fill::<T>(&caller, T::WindowSize::get().saturating_sub(1) as u32)?;
+        let device = register::<T>(&caller)?;
+        let record = record_of::<T>(s);
+        let timestamp: MomentOf<T> = 1u32.into();
+        let payload = attestation_payload(&caller, &record, &timestamp);
+        let signature = T::DeviceKeys::sign(&device, &payload);
+    }: _(RawOrigin::Signed(caller.clone()), record, timestamp, device.clone(), signature)
+    verify {
+        let seq = DatalogIndex::<T>::get(&caller).total() - 1;
+        assert_eq!(DatalogAttestation::<T>::get((&caller, seq)), Some(device));
+    }
+
+    register_device {
+        let caller = funded_caller::<T>();
+        let device = T::DeviceKeys::generate();
+        let genesis_hash = frame_system::Module::<T>::block_hash(T::BlockNumber::zero());
+        let payload = registration_payload(&caller, &genesis_hash);
+        let signature = T::DeviceKeys::sign(&device, &payload);
+    }: _(RawOrigin::Signed(caller), device.clone(), signature)
+    verify {
+        assert!(Devices::<T>::contains_key(&device));
+    }
+
+    unregister_device {
+        let caller = funded_caller::<T>();
+        let device = register::<T>(&caller)?;
+    }: _(RawOrigin::Signed(caller), device.clone())
+    verify {
+        assert!(!Devices::<T>::contains_key(&device));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{new_test_ext, Runtime};
+    use frame_support::assert_ok;
+
+    #[test]
+    fn test_benchmarks() {
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_record::<Runtime>());
+            assert_ok!(test_benchmark_erase::<Runtime>());
+            assert_ok!(test_benchmark_set_window_size::<Runtime>());
+            assert_ok!(test_benchmark_record_for::<Runtime>());
+            assert_ok!(test_benchmark_add_writer::<Runtime>());
+            assert_ok!(test_benchmark_remove_writer::<Runtime>());
+            assert_ok!(test_benchmark_send::<Runtime>());
+            assert_ok!(test_benchmark_allow_sender::<Runtime>());
+            assert_ok!(test_benchmark_disallow_sender::<Runtime>());
+            assert_ok!(test_benchmark_clear_inbox::<Runtime>());
+            assert_ok!(test_benchmark_erase_item::<Runtime>());
+            assert_ok!(test_benchmark_erase_older_than::<Runtime>());
+            assert_ok!(test_benchmark_set_retention::<Runtime>());
+            assert_ok!(test_benchmark_force_erase::<Runtime>());
+            assert_ok!(test_benchmark_freeze::<Runtime>());
+            assert_ok!(test_benchmark_thaw::<Runtime>());
+            assert_ok!(test_benchmark_record_attested::<Runtime>());
+            assert_ok!(test_benchmark_register_device::<Runtime>());
+            assert_ok!(test_benchmark_unregister_device::<Runtime>());
+            assert_ok!(test_benchmark_record_batch::<Runtime>());
+        });
+    }
+}
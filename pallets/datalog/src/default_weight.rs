@@ -1,20 +1,168 @@
+//! Weights for pallet_robonomics_datalog
+//!
+//! These are estimates, not benchmark results: storage access of every call is counted
+//! exactly, execution time is rounded up to multiples of 5 µs by comparison with similar
+//! calls of the frame pallets. Replace them with output of `benchmarking.rs` on reference
+//! hardware:
+//!
+//! ipci benchmark --chain dev --execution wasm --wasm-execution compiled \
+//!     --pallet pallet_robonomics_datalog --extrinsic '*' --steps 50 --repeat 20
+//!
+//! with the node built using `--features runtime-benchmarks`.
+
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
 use frame_support::weights::{constants::RocksDbWeight as DbWeight, Weight};
 
 pub trait WeightInfo {
-    fn record() -> Weight;
-    fn erase(win: u64) -> Weight;
+    fn record(s: u32) -> Weight;
+    fn erase(n: u32) -> Weight;
+    fn set_window_size(n: u32) -> Weight;
+    fn record_for(s: u32) -> Weight;
+    fn add_writer() -> Weight;
+    fn remove_writer() -> Weight;
+    fn send(s: u32) -> Weight;
+    fn allow_sender() -> Weight;
+    fn disallow_sender() -> Weight;
+    fn clear_inbox(n: u32) -> Weight;
+    fn erase_item(n: u32) -> Weight;
+    fn erase_older_than(n: u32) -> Weight;
+    fn set_retention() -> Weight;
+    fn force_erase(n: u32, a: u32) -> Weight;
+    fn freeze() -> Weight;
+    fn thaw() -> Weight;
+    fn record_attested(s: u32) -> Weight;
+    fn register_device() -> Weight;
+    fn unregister_device() -> Weight;
+    fn record_batch(n: u32, s: u32) -> Weight;
 }
 
 impl WeightInfo for () {
-    fn record() -> Weight {
-        (500_000 as Weight)
+    fn record(s: u32) -> Weight {
+        (100_000_000 as Weight)
+            .saturating_add((2_000 as Weight).saturating_mul(s as Weight))
+            .saturating_add(DbWeight::get().reads(8 as Weight))
+            .saturating_add(DbWeight::get().writes(10 as Weight))
+    }
+    fn erase(n: u32) -> Weight {
+        (30_000_000 as Weight)
+            .saturating_add((10_000_000 as Weight).saturating_mul(n as Weight))
+            .saturating_add(DbWeight::get().reads(3 as Weight))
+            .saturating_add(DbWeight::get().reads((1 as Weight).saturating_mul(n as Weight)))
+            .saturating_add(DbWeight::get().writes(2 as Weight))
+            .saturating_add(DbWeight::get().writes((2 as Weight).saturating_mul(n as Weight)))
+    }
+    fn set_window_size(n: u32) -> Weight {
+        (30_000_000 as Weight)
+            .saturating_add((10_000_000 as Weight).saturating_mul(n as Weight))
+            .saturating_add(DbWeight::get().reads(2 as Weight))
+            .saturating_add(DbWeight::get().reads((2 as Weight).saturating_mul(n as Weight)))
+            .saturating_add(DbWeight::get().writes(2 as Weight))
+            .saturating_add(DbWeight::get().writes((4 as Weight).saturating_mul(n as Weight)))
+    }
+    fn record_for(s: u32) -> Weight {
+        (105_000_000 as Weight)
+            .saturating_add((2_000 as Weight).saturating_mul(s as Weight))
+            .saturating_add(DbWeight::get().reads(9 as Weight))
+            .saturating_add(DbWeight::get().writes(11 as Weight))
+    }
+    fn add_writer() -> Weight {
+        (40_000_000 as Weight)
             .saturating_add(DbWeight::get().reads(2 as Weight))
             .saturating_add(DbWeight::get().writes(3 as Weight))
     }
-
-    fn erase(win: u64) -> Weight {
-        (100_000 as Weight)
+    fn remove_writer() -> Weight {
+        (40_000_000 as Weight)
+            .saturating_add(DbWeight::get().reads(3 as Weight))
+            .saturating_add(DbWeight::get().writes(3 as Weight))
+    }
+    fn send(s: u32) -> Weight {
+        (80_000_000 as Weight)
+            .saturating_add((2_000 as Weight).saturating_mul(s as Weight))
+            .saturating_add(DbWeight::get().reads(6 as Weight))
+            .saturating_add(DbWeight::get().writes(6 as Weight))
+    }
+    fn allow_sender() -> Weight {
+        (40_000_000 as Weight)
+            .saturating_add(DbWeight::get().reads(2 as Weight))
+            .saturating_add(DbWeight::get().writes(3 as Weight))
+    }
+    fn disallow_sender() -> Weight {
+        (40_000_000 as Weight)
+            .saturating_add(DbWeight::get().reads(3 as Weight))
+            .saturating_add(DbWeight::get().writes(3 as Weight))
+    }
+    fn clear_inbox(n: u32) -> Weight {
+        (30_000_000 as Weight)
+            .saturating_add((35_000_000 as Weight).saturating_mul(n as Weight))
+            .saturating_add(DbWeight::get().reads(1 as Weight))
+            .saturating_add(DbWeight::get().reads((3 as Weight).saturating_mul(n as Weight)))
+            .saturating_add(DbWeight::get().writes(1 as Weight))
+            .saturating_add(DbWeight::get().writes((3 as Weight).saturating_mul(n as Weight)))
+    }
+    fn erase_item(n: u32) -> Weight {
+        (55_000_000 as Weight)
+            .saturating_add((15_000_000 as Weight).saturating_mul(n as Weight))
+            .saturating_add(DbWeight::get().reads(4 as Weight))
+            .saturating_add(DbWeight::get().reads((2 as Weight).saturating_mul(n as Weight)))
+            .saturating_add(DbWeight::get().writes(3 as Weight))
+            .saturating_add(DbWeight::get().writes((4 as Weight).saturating_mul(n as Weight)))
+    }
+    fn erase_older_than(n: u32) -> Weight {
+        (25_000_000 as Weight)
+            .saturating_add((15_000_000 as Weight).saturating_mul(n as Weight))
+            .saturating_add(DbWeight::get().reads(3 as Weight))
+            .saturating_add(DbWeight::get().reads((2 as Weight).saturating_mul(n as Weight)))
+            .saturating_add(DbWeight::get().writes(1 as Weight))
+            .saturating_add(DbWeight::get().writes((2 as Weight).saturating_mul(n as Weight)))
+    }
+    fn set_retention() -> Weight {
+        (40_000_000 as Weight)
+            .saturating_add(DbWeight::get().reads(2 as Weight))
+            .saturating_add(DbWeight::get().writes(3 as Weight))
+    }
+    fn force_erase(n: u32, a: u32) -> Weight {
+        (30_000_000 as Weight)
+            .saturating_add((10_000_000 as Weight).saturating_mul(n as Weight))
+            .saturating_add((5_000_000 as Weight).saturating_mul(a as Weight))
+            .saturating_add(DbWeight::get().reads(2 as Weight))
+            .saturating_add(DbWeight::get().reads((1 as Weight).saturating_mul(n as Weight)))
+            .saturating_add(DbWeight::get().writes(2 as Weight))
+            .saturating_add(DbWeight::get().writes((2 as Weight).saturating_mul(n as Weight)))
+    }
+    fn freeze() -> Weight {
+        (20_000_000 as Weight)
+            .saturating_add(DbWeight::get().writes(1 as Weight))
+    }
+    fn thaw() -> Weight {
+        (20_000_000 as Weight)
             .saturating_add(DbWeight::get().reads(1 as Weight))
-            .saturating_add(DbWeight::get().writes(1+win as Weight))
+            .saturating_add(DbWeight::get().writes(1 as Weight))
+    }
+    fn record_attested(s: u32) -> Weight {
+        (190_000_000 as Weight)
+            .saturating_add((3_000 as Weight).saturating_mul(s as Weight))
+            .saturating_add(DbWeight::get().reads(10 as Weight))
+            .saturating_add(DbWeight::get().writes(12 as Weight))
+    }
+    fn register_device() -> Weight {
+        (100_000_000 as Weight)
+            .saturating_add(DbWeight::get().reads(3 as Weight))
+            .saturating_add(DbWeight::get().writes(3 as Weight))
     }
-}
\ No newline at end of file
+    fn unregister_device() -> Weight {
+        (40_000_000 as Weight)
+            .saturating_add(DbWeight::get().reads(3 as Weight))
+            .saturating_add(DbWeight::get().writes(3 as Weight))
+    }
+    fn record_batch(n: u32, s: u32) -> Weight {
+        (40_000_000 as Weight)
+            .saturating_add((120_000_000 as Weight).saturating_mul(n as Weight))
+            .saturating_add((3_000 as Weight).saturating_mul(s as Weight))
+            .saturating_add(DbWeight::get().reads(4 as Weight))
+            .saturating_add(DbWeight::get().reads((6 as Weight).saturating_mul(n as Weight)))
+            .saturating_add(DbWeight::get().writes(2 as Weight))
+            .saturating_add(DbWeight::get().writes((8 as Weight).saturating_mul(n as Weight)))
+    }
+}
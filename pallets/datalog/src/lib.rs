@@ -20,53 +20,100 @@
 //use codec::{Codec, Encode, Decode, EncodeLike};
 use frame_support::{
     codec::{Codec, Decode, Encode, EncodeLike},
-    decl_error, decl_event, decl_module, decl_storage, ensure,
-    sp_runtime::traits::Member,
+    decl_error, decl_event, decl_module, decl_storage, ensure, Parameter,
+    sp_runtime::{
+        traits::{IdentifyAccount, Member, Saturating, Verify, Zero},
+        RuntimeDebug,
+    },
     sp_std::prelude::*,
-    traits::{Get, Time},
+    traits::{Currency, EnsureOrigin, Get, ReservableCurrency, Time},
+    weights::Weight,
 };
+use frame_support::dispatch::{DispatchError, DispatchResult, DispatchResultWithPostInfo};
 use frame_system::ensure_signed;
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
 pub use default_weight::WeightInfo;
+pub use mmr::MerkleProof;
+pub use record::{Content, Format, TypedRecord};
+mod benchmarking;
 mod default_weight;
+pub mod migration;
+pub mod mmr;
+pub mod record;
 
 /// Type synonym for timestamp data type.
 pub type MomentOf<T> = <<T as Trait>::Time as Time>::Moment;
 /// system::AccountId type
 pub type AccountIdOf<T> = <T as frame_system::Trait>::AccountId;
+/// Balance type of deposit currency.
+pub type BalanceOf<T> = <<T as Trait>::Currency as Currency<AccountIdOf<T>>>::Balance;
 
 #[cfg_attr(feature = "std", derive(Debug, PartialEq))]
 #[derive(Encode, Decode)]
 pub struct RingBufferItem<T: Trait>(
     #[codec(compact)] <<T as Trait>::Time as Time>::Moment,
     <T as Trait>::Record,
+    /// Sequence number of record in account`s datalog.
+    #[codec(compact)]
+    u64,
+    /// Block the record was written in, unknown for records of previous releases.
+    Option<<T as frame_system::Trait>::BlockNumber>,
 );
 
 impl<T: Trait> Default for RingBufferItem<T> {
     fn default() -> Self {
-        Self(Default::default(), Default::default())
+        Self(Default::default(), Default::default(), 0, None)
     }
 }
 
 #[cfg(test)]
 impl<T: Trait> RingBufferItem<T> {
-    fn new(now: <<T as Trait>::Time as Time>::Moment, record: <T as Trait>::Record) -> Self {
-        Self(now, record)
+    /// Item written in the current block.
+    fn new(
+        now: <<T as Trait>::Time as Time>::Moment,
+        record: <T as Trait>::Record,
+        seq: u64,
+    ) -> Self {
+        Self(now, record, seq, Some(<frame_system::Module<T>>::block_number()))
+    }
+
+    /// Item of previous releases, written in unknown block.
+    fn legacy(
+        now: <<T as Trait>::Time as Time>::Moment,
+        record: <T as Trait>::Record,
+        seq: u64,
+    ) -> Self {
+        Self(now, record, seq, None)
     }
 }
 
 impl<T: Trait> RingBufferItem<T> {
-    fn into(self) -> (<<T as Trait>::Time as Time>::Moment, <T as Trait>::Record) {
+    pub fn into(self) -> (<<T as Trait>::Time as Time>::Moment, <T as Trait>::Record) {
         (self.0, self.1)
     }
+
+    /// Sequence number of record in account`s datalog.
+    pub fn seq(&self) -> u64 {
+        self.2
+    }
+
+    /// Block the record was written in, if known.
+    pub fn block(&self) -> Option<<T as frame_system::Trait>::BlockNumber> {
+        self.3
+    }
 }
 
-#[cfg_attr(feature = "std", derive(Debug, PartialEq))]
+#[cfg_attr(feature = "std", derive(Debug, PartialEq, Serialize, Deserialize))]
 #[derive(Encode, Decode, Default)]
 pub struct RingBufferIndex {
     #[codec(compact)]
     start: u64,
     #[codec(compact)]
     end: u64,
+    /// Number of records ever written, sequence number of the next record.
+    #[codec(compact)]
+    total: u64,
 }
 
 impl RingBufferIndex {
@@ -79,6 +126,7 @@ impl RingBufferIndex {
     }
 
     pub fn add(&mut self, max: u64) -> u64 {
+        self.total = self.total.saturating_add(1);
         let v = self.end;
         Self::next(&mut self.end, max);
         if self.start == self.end {
@@ -87,6 +135,30 @@ impl RingBufferIndex {
         v
     }
 
+    /// Number of items stored in the buffer.
+    pub fn len(&self, max: u64) -> u64 {
+        if self.end >= self.start {
+            self.end - self.start
+        } else {
+            max - self.start + self.end
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// Number of records ever written into the buffer.
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// Slot of the `n`-th item counting from the oldest one.
+    #[inline]
+    fn nth(&self, n: u64, max: u64) -> u64 {
+        (self.start + n) % max
+    }
+
     fn iter(&mut self, max: u64) -> RingBufferIterator<'_> {
         RingBufferIterator { inner: self, max }
     }
@@ -110,18 +182,238 @@ impl Iterator for RingBufferIterator<'_> {
     }
 }
 
+/// Record sent into account`s inbox.
+#[cfg_attr(feature = "std", derive(Debug, PartialEq))]
+#[derive(Encode, Decode)]
+pub struct InboxItem<T: Trait>(
+    <T as frame_system::Trait>::AccountId,
+    #[codec(compact)] <<T as Trait>::Time as Time>::Moment,
+    <T as Trait>::Record,
+);
+
+impl<T: Trait> Default for InboxItem<T> {
+    fn default() -> Self {
+        Self(Default::default(), Default::default(), Default::default())
+    }
+}
+
+impl<T: Trait> InboxItem<T> {
+    pub fn into(
+        self,
+    ) -> (
+        <T as frame_system::Trait>::AccountId,
+        <<T as Trait>::Time as Time>::Moment,
+        <T as Trait>::Record,
+    ) {
+        (self.0, self.1, self.2)
+    }
+}
+
+/// Device registered to sign records of account.
+#[cfg_attr(feature = "std", derive(Debug, PartialEq))]
+#[derive(Encode, Decode, Default, Clone)]
+pub struct DeviceInfo<AccountId, Moment> {
+    /// Account the device belongs to.
+    pub owner: AccountId,
+    /// Timestamp of the latest reading attested by the device.
+    pub last_timestamp: Moment,
+}
+
+/// Datalog record with its attestation status.
+#[cfg_attr(feature = "std", derive(Debug, PartialEq, Serialize, Deserialize))]
+#[derive(Encode, Decode, Clone)]
+pub struct Entry<AccountId, Moment, Record> {
+    /// Sequence number of record.
+    pub seq: u64,
+    /// Record timestamp.
+    pub moment: Moment,
+    /// Record itself.
+    pub record: Record,
+    /// Device that signed the record, if any.
+    pub attested_by: Option<AccountId>,
+}
+
+/// Change of account datalog made in a block, as seen by subscribers.
+#[cfg_attr(feature = "std", derive(Debug, PartialEq, Serialize, Deserialize))]
+#[derive(Encode, Decode, Clone)]
+pub enum RecordChange<AccountId, Moment, Record> {
+    /// Record added: [account, moment, record, sequence number].
+    NewRecord(AccountId, Moment, Record, u64),
+    /// Batch of records added: [account, first sequence number, count].
+    BatchRecorded(AccountId, u64, u32),
+    /// Datalog erased by account.
+    Erased(AccountId),
+    /// Datalog erased by force origin.
+    ForceErased(AccountId),
+    /// Record with given sequence number erased.
+    RecordErased(AccountId, u64),
+    /// Records older than given moment erased.
+    ErasedOlderThan(AccountId, Moment),
+    /// Record attested by device: [account, sequence number, device].
+    RecordAttested(AccountId, u64, AccountId),
+}
+
+impl<AccountId, Moment, Record> RecordChange<AccountId, Moment, Record> {
+    /// Account whose datalog is changed.
+    pub fn account(&self) -> &AccountId {
+        match self {
+            Self::NewRecord(account, ..)
+            | Self::BatchRecorded(account, ..)
+            | Self::Erased(account)
+            | Self::ForceErased(account)
+            | Self::RecordErased(account, ..)
+            | Self::ErasedOlderThan(account, ..)
+            | Self::RecordAttested(account, ..) => account,
+        }
+    }
+}
+
+impl<AccountId, BlockNumber, Moment, Record, Balance>
+    RawEvent<AccountId, BlockNumber, Moment, Record, Balance>
+{
+    /// Datalog change described by event, if any.
+    pub fn record_change(self) -> Option<RecordChange<AccountId, Moment, Record>> {
+        match self {
+            Self::NewRecord(account, moment, record, seq, _) => {
+                Some(RecordChange::NewRecord(account, moment, record, seq))
+            }
+            Self::BatchRecorded(account, first, count, _) => {
+                Some(RecordChange::BatchRecorded(account, first, count))
+            }
+            Self::Erased(account) => Some(RecordChange::Erased(account)),
+            Self::ForceErased(account) => Some(RecordChange::ForceErased(account)),
+            Self::RecordErased(account, seq) => Some(RecordChange::RecordErased(account, seq)),
+            Self::ErasedOlderThan(account, moment) => {
+                Some(RecordChange::ErasedOlderThan(account, moment))
+            }
+            Self::RecordAttested(account, seq, device) => {
+                Some(RecordChange::RecordAttested(account, seq, device))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Payload signed by device to attest a reading for account.
+pub fn attestation_payload<AccountId: Encode, Record: Encode, Moment: Encode>(
+    account: &AccountId,
+    record: &Record,
+    timestamp: &Moment,
+) -> Vec<u8> {
+    (account, record, timestamp).encode()
+}
+
+/// Payload signed by device to prove its key when registered to `owner`.
+///
+/// Genesis hash binds the proof to the chain, so it can't be replayed on another network.
+pub fn registration_payload<AccountId: Encode, Hash: Encode>(
+    owner: &AccountId,
+    genesis_hash: &Hash,
+) -> Vec<u8> {
+    (b"datalog:device", owner, genesis_hash).encode()
+}
+
+/// Device keys attesting records in benchmarks.
+#[cfg(feature = "runtime-benchmarks")]
+pub trait DeviceKeys<AccountId, Signature> {
+    /// Generate new device key, returns account of the device.
+    fn generate() -> AccountId;
+    /// Sign payload with key of the device.
+    fn sign(device: &AccountId, payload: &[u8]) -> Signature;
+}
+
+/// Delegate allowed to write into account`s datalog.
+#[cfg_attr(feature = "std", derive(Debug, PartialEq))]
+#[derive(Encode, Decode, Default, Clone)]
+pub struct WriterInfo<BlockNumber> {
+    /// Block after which the writer is no longer allowed to write.
+    pub expiry: Option<BlockNumber>,
+    /// Minimal number of blocks between two records of the writer.
+    pub min_interval: BlockNumber,
+    /// Block of the latest record of the writer.
+    pub last_write: Option<BlockNumber>,
+}
+
+/// Storage layout version, used for runtime upgrades.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, RuntimeDebug)]
+enum Releases {
+    /// Time tagged vectors in `Datalog` map and ring buffer of raw records.
+    V1,
+    /// Ring buffer of sequence numbered records committed into history.
+    V2,
+}
+
+impl Default for Releases {
+    fn default() -> Self {
+        Releases::V1
+    }
+}
+
+/// Prefix of datalog archive keys in offchain indexing storage.
+pub const ARCHIVE_PREFIX: &[u8] = b"datalog::archive";
+
+/// Offchain indexing key of `seq`-th record of account in datalog archive.
+///
+/// Offchain indexing is done on import of every block, non-canonical ones included,
+/// so the value could be written by a block of abandoned fork. Archived records should
+/// be checked against merkle mountain range root of the canonical chain.
+pub fn archive_key<AccountId: Encode>(account: &AccountId, seq: u64) -> Vec<u8> {
+    (ARCHIVE_PREFIX, account, seq).encode()
+}
+
+/// Prefix of merkle mountain range node keys in offchain indexing storage.
+pub const MMR_NODE_PREFIX: &[u8] = b"datalog::mmr";
+
+/// Offchain indexing key of merkle mountain range node of account at position `pos`.
+pub fn mmr_node_key<AccountId: Encode>(account: &AccountId, pos: u64) -> Vec<u8> {
+    (MMR_NODE_PREFIX, account, pos).encode()
+}
+
 /// Datalog module main trait.
 pub trait Trait: frame_system::Trait {
     /// Timestamp source.
     type Time: Time;
-    /// Datalog record data type.
-    type Record: Codec + EncodeLike + Member + Default;
+    /// Datalog record data type, raw records of previous releases are converted using `From`.
+    type Record: Codec + EncodeLike + Member + Default + Content + From<Vec<u8>>;
     /// The overarching event type.
     type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
-    /// log window
+    /// default log window
     type WindowSize: Get<u64>;
+    /// maximum log window account could choose
+    type MaximumWindowSize: Get<u64>;
     /// maximum record length
     type MaximumMessageSize: Get<usize>;
+    /// Currency used for record deposits.
+    type Currency: ReservableCurrency<Self::AccountId>;
+    /// Deposit reserved for each filled ring buffer slot.
+    type DepositPerItem: Get<BalanceOf<Self>>;
+    /// Deposit reserved for each byte of encoded record.
+    type DepositPerByte: Get<BalanceOf<Self>>;
+    /// Deposit reserved from owner for each writer allowed to record into its datalog.
+    type WriterDeposit: Get<BalanceOf<Self>>;
+    /// Deposit reserved from recipient for each sender allowed to send into its inbox.
+    type SenderDeposit: Get<BalanceOf<Self>>;
+    /// Deposit reserved from account while its retention period is set.
+    type RetentionDeposit: Get<BalanceOf<Self>>;
+    /// Deposit reserved from owner for each registered device.
+    type DeviceDeposit: Get<BalanceOf<Self>>;
+    /// Maximum number of expired records dropped by one `record` call.
+    type MaxPrunePerRecord: Get<u32>;
+    /// Maximum number of records in one `record_batch` call.
+    type MaxBatchLength: Get<u32>;
+    /// Emit `NewRecord` for every record of batch instead of single `BatchRecorded` event.
+    type BatchRecordEvents: Get<bool>;
+    /// Maximum number of accounts migrated to the current storage layout in one block.
+    type MaxMigratedPerBlock: Get<u32>;
+    /// Origin allowed to erase and freeze datalog of any account.
+    type ForceOrigin: EnsureOrigin<Self::Origin>;
+    /// Signature of device attesting a record.
+    type Signature: Verify<Signer = Self::Public> + Parameter;
+    /// Public key of device, identified by account id.
+    type Public: IdentifyAccount<AccountId = Self::AccountId>;
+    /// Device keys signing attested records in benchmarks.
+    #[cfg(feature = "runtime-benchmarks")]
+    type DeviceKeys: DeviceKeys<Self::AccountId, Self::Signature>;
     /// extrinsic weights
     type WeightInfo: WeightInfo;
 }
@@ -129,35 +421,163 @@ decl_error! {
     pub enum Error for Module<T: Trait> {
         /// Potentially dangerous action
         RecordTooBig,
+        /// Window size is out of allowed range.
+        BadWindowSize,
+        /// Record content doesn't match its format.
+        InvalidRecord,
+        /// Sender isn't allowed to write into this datalog.
+        NotAuthorized,
+        /// Writer permission is expired.
+        WriterExpired,
+        /// Writer records more often than allowed.
+        TooFrequent,
+        /// Recipient doesn't accept records from sender.
+        SenderNotAllowed,
+        /// There is no record with given sequence number.
+        RecordNotFound,
+        /// Account is frozen and can't write records.
+        AccountFrozen,
+        /// Account is not frozen.
+        NotFrozen,
+        /// Device is not registered to the account.
+        DeviceNotRegistered,
+        /// Device is already registered.
+        DeviceAlreadyRegistered,
+        /// Device signature doesn't match the signed payload.
+        BadSignature,
+        /// Attested reading is not newer than the previous one of the device.
+        StaleAttestation,
+        /// Batch has more records than allowed.
+        BatchTooLong,
+        /// Datalog storage is being migrated, try again in a few blocks.
+        Migrating,
     }
 }
 
 decl_event! {
     pub enum Event<T>
     where AccountId = <T as frame_system::Trait>::AccountId,
+          BlockNumber = <T as frame_system::Trait>::BlockNumber,
           Moment = MomentOf<T>,
           Record = <T as Trait>::Record,
+          Balance = BalanceOf<T>,
     {
-        /// New data added.
-        NewRecord(AccountId, Moment, Record),
+        /// New data added: [account, moment, record, sequence number, block number].
+        NewRecord(AccountId, Moment, Record, u64, BlockNumber),
+        /// Batch of records added: [account, first sequence number, count, block number].
+        BatchRecorded(AccountId, u64, u32, BlockNumber),
         /// Account datalog erased.
         Erased(AccountId),
-        /// Record sender to another location.
-        RecordSent(AccountId),
+        /// Deposit reserved for new record.
+        DepositReserved(AccountId, Balance),
+        /// Deposit of overwritten or erased records returned.
+        DepositReturned(AccountId, Balance),
+        /// Account datalog window size changed.
+        WindowSizeChanged(AccountId, u64),
+        /// Record sent into inbox of another account: [sender, recipient, moment, record].
+        RecordSent(AccountId, AccountId, Moment, Record),
+        /// Sender allowed to send records into inbox: [recipient, sender].
+        SenderAllowed(AccountId, AccountId),
+        /// Sender disallowed to send records into inbox: [recipient, sender].
+        SenderDisallowed(AccountId, AccountId),
+        /// Account inbox cleared.
+        InboxCleared(AccountId),
+        /// Record with given sequence number erased.
+        RecordErased(AccountId, u64),
+        /// Records older than given moment erased.
+        ErasedOlderThan(AccountId, Moment),
+        /// Account retention period changed.
+        RetentionChanged(AccountId, Option<Moment>),
+        /// Account datalog erased by force origin.
+        ForceErased(AccountId),
+        /// Account frozen by force origin.
+        Frozen(AccountId),
+        /// Account thawed by force origin.
+        Thawed(AccountId),
+        /// Device registered to account: [owner, device].
+        DeviceRegistered(AccountId, AccountId),
+        /// Device unregistered: [owner, device].
+        DeviceUnregistered(AccountId, AccountId),
+        /// Record attested by device: [account, sequence number, device].
+        RecordAttested(AccountId, u64, AccountId),
+        /// Writer allowed to record into owner`s datalog: [owner, writer].
+        WriterAdded(AccountId, AccountId),
+        /// Writer permission removed: [owner, writer].
+        WriterRemoved(AccountId, AccountId),
     }
 }
 
 decl_storage! {
     trait Store for Module<T: Trait> as Datalog {
-        /// Time tagged data of given account (old values).
+        /// Time tagged data of given account (old values, removed by migration).
         Datalog get(fn datalog): map hasher(blake2_128_concat)
                                  T::AccountId => Vec<(MomentOf<T>, T::Record)>;
         /// Ringbuffer start/end pointers
         DatalogIndex get(fn datalogidx): map hasher(twox_64_concat)
                                  T::AccountId => RingBufferIndex;
+        /// Ringbuffer window size chosen by account
+        DatalogWindow get(fn datalogwindow): map hasher(twox_64_concat)
+                                 T::AccountId => Option<u64>;
         /// Ringbuffer items
         DatalogItem get(fn datalogitem): map hasher(twox_64_concat)
                                  (T::AccountId, u64) => RingBufferItem::<T>;
+        /// Deposit reserved for ringbuffer item
+        DatalogDeposit get(fn datalogdeposit): map hasher(twox_64_concat)
+                                 (T::AccountId, u64) => BalanceOf<T>;
+        /// Delegates allowed to write into account`s datalog.
+        Writers get(fn writers): double_map hasher(twox_64_concat) T::AccountId,
+                                 hasher(twox_64_concat) T::AccountId
+                                 => Option<WriterInfo<T::BlockNumber>>;
+        /// Deposit reserved from owner for writer permission.
+        DatalogWriterDeposit get(fn writer_deposit): double_map hasher(twox_64_concat)
+                                 T::AccountId, hasher(twox_64_concat) T::AccountId
+                                 => BalanceOf<T>;
+        /// Number of leaves in merkle mountain range of account`s records.
+        DatalogMmrLeaves get(fn mmr_leaves): map hasher(twox_64_concat)
+                                 T::AccountId => u64;
+        /// Peaks of merkle mountain range of account`s records, highest first.
+        DatalogMmrPeaks get(fn mmr_peaks): map hasher(twox_64_concat)
+                                 T::AccountId => Vec<T::Hash>;
+        /// Root of merkle mountain range of account`s records.
+        DatalogMmrRoot get(fn mmr_root): map hasher(twox_64_concat)
+                                 T::AccountId => T::Hash;
+        /// Devices allowed to attest records of their owners.
+        Devices get(fn devices): map hasher(twox_64_concat)
+                                 T::AccountId => Option<DeviceInfo<T::AccountId, MomentOf<T>>>;
+        /// Deposit reserved from owner for registered device.
+        DatalogDeviceDeposit get(fn device_deposit): map hasher(twox_64_concat)
+                                 T::AccountId => BalanceOf<T>;
+        /// Device that attested record with given sequence number.
+        DatalogAttestation get(fn attestation): map hasher(twox_64_concat)
+                                 (T::AccountId, u64) => Option<T::AccountId>;
+        /// Accounts not allowed to write records.
+        Frozen get(fn frozen): map hasher(twox_64_concat) T::AccountId => bool;
+        /// Period after which records of account are dropped.
+        DatalogRetention get(fn retention): map hasher(twox_64_concat)
+                                 T::AccountId => Option<MomentOf<T>>;
+        /// Deposit reserved for retention period of account.
+        DatalogRetentionDeposit get(fn retention_deposit): map hasher(twox_64_concat)
+                                 T::AccountId => BalanceOf<T>;
+        /// Senders allowed to send records into account`s inbox.
+        InboxSenders get(fn inbox_senders): double_map hasher(twox_64_concat) T::AccountId,
+                                 hasher(twox_64_concat) T::AccountId => bool;
+        /// Deposit reserved from recipient for allowed sender.
+        InboxSenderDeposit get(fn sender_deposit): double_map hasher(twox_64_concat)
+                                 T::AccountId, hasher(twox_64_concat) T::AccountId
+                                 => BalanceOf<T>;
+        /// Inbox ringbuffer start/end pointers
+        InboxIndex get(fn inboxidx): map hasher(twox_64_concat)
+                                 T::AccountId => RingBufferIndex;
+        /// Inbox ringbuffer items
+        InboxItems get(fn inboxitem): map hasher(twox_64_concat)
+                                 (T::AccountId, u64) => InboxItem::<T>;
+        /// Deposit reserved from sender for inbox ringbuffer item
+        InboxDeposit get(fn inboxdeposit): map hasher(twox_64_concat)
+                                 (T::AccountId, u64) => BalanceOf<T>;
+        /// Storage layout version.
+        StorageVersion build(|_: &GenesisConfig| Releases::V2): Releases;
+        /// Raw storage key the storage migration continues after.
+        MigrationCursor: Option<Vec<u8>>;
     }
 }
 
@@ -165,223 +585,1981 @@ decl_module! {
     pub struct Module<T: Trait> for enum Call where origin: T::Origin {
         fn deposit_event() = default;
 
+        fn on_initialize(_n: T::BlockNumber) -> Weight {
+            // records are not accessible until all accounts are migrated
+            if StorageVersion::get() == Releases::V2 {
+                T::DbWeight::get().reads(1)
+            } else {
+                migration::migrate_step::<T>()
+            }
+        }
+
         /// Store new data into blockchain.
-        #[weight = <T as Trait>::WeightInfo::record()]
+        #[weight = <T as Trait>::WeightInfo::record(record.size_hint() as u32)
+            .saturating_add(<T as Trait>::WeightInfo::erase_older_than(T::MaxPrunePerRecord::get()))]
         fn record(origin, record: T::Record) {
             ensure!(record.size_hint() <= T::MaximumMessageSize::get(), Error::<T>::RecordTooBig );
+            ensure!(record.is_valid(), Error::<T>::InvalidRecord );
             let sender = ensure_signed(origin)?;
+            Self::ensure_migrated()?;
+            ensure!(!Frozen::<T>::get(&sender), Error::<T>::AccountFrozen);
 
-            // remove previous version
-            Datalog::<T>::remove(&sender);
-            let now = T::Time::now();
-            let item = RingBufferItem( now, record );
+            Self::insert_record(sender, record)?;
+        }
+
+        /// Store batch of records in order, deposit for all of them is reserved at once.
+        ///
+        /// Weight depends on number of records and their total size.
+        #[weight = <T as Trait>::WeightInfo::record_batch(
+                records.len() as u32,
+                records.iter().map(|record| record.size_hint() as u32).sum(),
+            )
+            .saturating_add(
+                <T as Trait>::WeightInfo::erase_older_than(T::MaxPrunePerRecord::get())
+                    .saturating_mul(records.len() as Weight)
+            )]
+        fn record_batch(origin, records: Vec<T::Record>) {
+            ensure!(records.len() <= T::MaxBatchLength::get() as usize, Error::<T>::BatchTooLong);
+            for record in records.iter() {
+                ensure!(record.size_hint() <= T::MaximumMessageSize::get(), Error::<T>::RecordTooBig );
+                ensure!(record.is_valid(), Error::<T>::InvalidRecord );
+            }
+            let sender = ensure_signed(origin)?;
+            Self::ensure_migrated()?;
+            ensure!(!Frozen::<T>::get(&sender), Error::<T>::AccountFrozen);
 
-            DatalogIndex::<T>::mutate(&sender, |idx|{
-                let window_size = T::WindowSize::get();
-                let end = idx.add( window_size );
+            let deposits: Vec<_> = records.iter().map(Self::deposit_of).collect();
+            let deposit = deposits
+                .iter()
+                .fold(BalanceOf::<T>::zero(), |total, deposit| total.saturating_add(*deposit));
+            T::Currency::reserve(&sender, deposit)?;
 
-                DatalogItem::<T>::insert( (&sender, end), &item )
-            });
+            let block = <frame_system::Module<T>>::block_number();
+            let count = records.len() as u32;
+            let mut first = None;
+            let mut returned: BalanceOf<T> = Zero::zero();
+            for (record, deposit) in records.into_iter().zip(deposits) {
+                let (item, dropped) = Self::append(&sender, record, deposit);
+                returned = returned.saturating_add(dropped);
+                let seq = item.seq();
+                first = first.or(Some(seq));
+                if T::BatchRecordEvents::get() {
+                    let (now, record) = item.into();
+                    Self::deposit_event(RawEvent::NewRecord(sender.clone(), now, record, seq, block));
+                }
+            }
 
-            let ( now, record ) = item.into();
-            Self::deposit_event(RawEvent::NewRecord( sender, now, record ));
+            if let Some(first) = first.filter(|_| !T::BatchRecordEvents::get()) {
+                Self::deposit_event(RawEvent::BatchRecorded(sender.clone(), first, count, block));
+            }
+            if !deposit.is_zero() {
+                Self::deposit_event(RawEvent::DepositReserved(sender.clone(), deposit));
+            }
+            if !returned.is_zero() {
+                Self::deposit_event(RawEvent::DepositReturned(sender, returned));
+            }
         }
 
-        /// Clear account`s datalog.
-        #[weight = <T as Trait>::WeightInfo::erase(T::WindowSize::get())]
-        fn erase(origin) {
+        /// Store new data into datalog of `owner` as its delegated writer.
+        ///
+        /// Record deposit is reserved from `owner`.
+        #[weight = <T as Trait>::WeightInfo::record_for(record.size_hint() as u32)
+            .saturating_add(<T as Trait>::WeightInfo::erase_older_than(T::MaxPrunePerRecord::get()))]
+        fn record_for(origin, owner: T::AccountId, record: T::Record) {
+            ensure!(record.size_hint() <= T::MaximumMessageSize::get(), Error::<T>::RecordTooBig );
+            ensure!(record.is_valid(), Error::<T>::InvalidRecord );
             let sender = ensure_signed(origin)?;
-            Datalog::<T>::remove(&sender);
+            Self::ensure_migrated()?;
+            ensure!(
+                !Frozen::<T>::get(&sender) && !Frozen::<T>::get(&owner),
+                Error::<T>::AccountFrozen
+            );
 
-            let mut idx = DatalogIndex::<T>::take(&sender);
-            let window_size = T::WindowSize::get();
-            for start in idx.iter(window_size){
-                DatalogItem::<T>::remove( (&sender, start) )
+            let now = <frame_system::Module<T>>::block_number();
+            let mut info = Writers::<T>::get(&owner, &sender).ok_or(Error::<T>::NotAuthorized)?;
+            if let Some(expiry) = info.expiry {
+                ensure!(now <= expiry, Error::<T>::WriterExpired);
+            }
+            if let Some(last_write) = info.last_write {
+                ensure!(
+                    now >= last_write.saturating_add(info.min_interval),
+                    Error::<T>::TooFrequent
+                );
             }
 
-            Self::deposit_event(RawEvent::Erased(sender));
+            // the latest write is noted only when the record is stored
+            Self::insert_record(owner.clone(), record)?;
+            info.last_write = Some(now);
+            Writers::<T>::insert(&owner, &sender, info);
         }
-    }
-}
-
-impl<T: Trait> Module<T> {
-    pub fn data(account: &T::AccountId) -> Vec<RingBufferItem<T>> {
-        let mut idx = DatalogIndex::<T>::get(&account);
-        let window_size = T::WindowSize::get();
 
-        idx.iter(window_size)
-            .map(|i| DatalogItem::<T>::get((&account, i)))
-            .collect()
-    }
-}
+        /// Store new record signed by a device registered to sender.
+        ///
+        /// Device signs `attestation_payload(sender, record, timestamp)`,
+        /// where `timestamp` is the moment of reading.
+        #[weight = <T as Trait>::WeightInfo::record_attested(record.size_hint() as u32)
+            .saturating_add(<T as Trait>::WeightInfo::erase_older_than(T::MaxPrunePerRecord::get()))]
+        fn record_attested(
+            origin,
+            record: T::Record,
+            timestamp: MomentOf<T>,
+            device: T::AccountId,
+            signature: T::Signature,
+        ) {
+            ensure!(record.size_hint() <= T::MaximumMessageSize::get(), Error::<T>::RecordTooBig );
+            ensure!(record.is_valid(), Error::<T>::InvalidRecord );
+            let sender = ensure_signed(origin)?;
+            Self::ensure_migrated()?;
+            ensure!(!Frozen::<T>::get(&sender), Error::<T>::AccountFrozen);
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+            let mut info = Devices::<T>::get(&device)
+                .filter(|info| info.owner == sender)
+                .ok_or(Error::<T>::DeviceNotRegistered)?;
+            ensure!(timestamp > info.last_timestamp, Error::<T>::StaleAttestation);
+            let payload = attestation_payload(&sender, &record, &timestamp);
+            ensure!(signature.verify(&payload[..], &device), Error::<T>::BadSignature);
 
-    use base58::FromBase58;
-    use frame_support::sp_runtime::{
-        testing::Header, traits::IdentityLookup, DispatchError, Perbill,
-    };
-    use frame_support::{
-        assert_err, assert_ok, impl_outer_origin, parameter_types,
-        weights::{
-            constants::{BlockExecutionWeight, ExtrinsicBaseWeight},
-            Weight,
-        },
-    };
-    use node_primitives::Moment;
-    use sp_core::H256;
+            let seq = Self::insert_record(sender.clone(), record)?;
+            info.last_timestamp = timestamp;
+            Devices::<T>::insert(&device, info);
+            DatalogAttestation::<T>::insert((&sender, seq), &device);
+            Self::deposit_event(RawEvent::RecordAttested(sender, seq, device));
+        }
 
-    impl_outer_origin! {
-        pub enum Origin for Runtime {}
-    }
+        /// Register device allowed to attest records of sender.
+        ///
+        /// Device proves its key by `signature` over `registration_payload` of sender.
+        /// Device deposit is reserved from sender until the device is unregistered.
+        #[weight = <T as Trait>::WeightInfo::register_device()]
+        fn register_device(origin, device: T::AccountId, signature: T::Signature) {
+            let sender = ensure_signed(origin)?;
+            ensure!(!Devices::<T>::contains_key(&device), Error::<T>::DeviceAlreadyRegistered);
+            let genesis_hash = <frame_system::Module<T>>::block_hash(T::BlockNumber::zero());
+            let payload = registration_payload(&sender, &genesis_hash);
+            ensure!(signature.verify(&payload[..], &device), Error::<T>::BadSignature);
 
-    #[derive(Clone, PartialEq, Eq, Debug)]
-    pub struct Runtime;
-    type Datalog = Module<Runtime>;
-    type RuntimeError = Error<Runtime>;
-    type Timestamp = pallet_timestamp::Module<Runtime>;
-    type Item = RingBufferItem<Runtime>;
+            let deposit = T::DeviceDeposit::get();
+            T::Currency::reserve(&sender, deposit)?;
+            DatalogDeviceDeposit::<T>::insert(&device, deposit);
 
-    parameter_types! {
-        pub const BlockHashCount: u64 = 250;
+            let info = DeviceInfo { owner: sender.clone(), last_timestamp: Default::default() };
+            Devices::<T>::insert(&device, info);
+            Self::deposit_event(RawEvent::DeviceRegistered(sender.clone(), device));
+            if !deposit.is_zero() {
+                Self::deposit_event(RawEvent::DepositReserved(sender, deposit));
+            }
+        }
 
-        pub const MaximumBlockWeight: Weight = 2_000_000_000;
-        pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
+        /// Unregister device of sender, records it attested stay attested.
+        ///
+        /// Device deposit is returned to sender.
+        #[weight = <T as Trait>::WeightInfo::unregister_device()]
+        fn unregister_device(origin, device: T::AccountId) {
+            let sender = ensure_signed(origin)?;
+            ensure!(
+                Devices::<T>::get(&device).filter(|info| info.owner == sender).is_some(),
+                Error::<T>::DeviceNotRegistered
+            );
 
-        pub MaximumExtrinsicWeight: Weight = 1_000_000_000;
-        pub const MaximumBlockLength: u32 = 1_000_000;
-    }
+            Devices::<T>::remove(&device);
+            let deposit = DatalogDeviceDeposit::<T>::take(&device);
+            T::Currency::unreserve(&sender, deposit);
+            Self::deposit_event(RawEvent::DeviceUnregistered(sender.clone(), device));
+            if !deposit.is_zero() {
+                Self::deposit_event(RawEvent::DepositReturned(sender, deposit));
+            }
+        }
 
-    impl frame_system::Trait for Runtime {
-        type Origin = Origin;
-        type Index = u64;
-        type BlockNumber = u64;
-        type Call = ();
-        type Hash = H256;
-        type Hashing = frame_support::sp_runtime::traits::BlakeTwo256;
-        type AccountId = u64;
-        type Lookup = IdentityLookup<Self::AccountId>;
-        type Header = Header;
-        type Event = ();
-        type BlockHashCount = BlockHashCount;
-        type Version = ();
-        type PalletInfo = ();
-        type AccountData = ();
-        type OnNewAccount = ();
-        type OnKilledAccount = ();
-        type DbWeight = ();
-        type BaseCallFilter = ();
-        type SystemWeightInfo = ();
-        type BlockExecutionWeight = BlockExecutionWeight;
-        type ExtrinsicBaseWeight = ExtrinsicBaseWeight;
-        type MaximumExtrinsicWeight = MaximumExtrinsicWeight;
-        type MaximumBlockWeight = MaximumBlockWeight;
-        type MaximumBlockLength = MaximumBlockLength;
-        type AvailableBlockRatio = AvailableBlockRatio;
-    }
+        /// Allow `writer` to record into sender`s datalog.
+        ///
+        /// Writer permission ends after `expiry` block if given, writer records are
+        /// limited to one per `min_interval` blocks. Existing permission is replaced.
+        /// Writer deposit is reserved from sender for new permission until it is removed.
+        #[weight = <T as Trait>::WeightInfo::add_writer()]
+        fn add_writer(
+            origin,
+            writer: T::AccountId,
+            expiry: Option<T::BlockNumber>,
+            min_interval: T::BlockNumber,
+        ) {
+            let sender = ensure_signed(origin)?;
 
-    parameter_types! {
-        pub const MinimumPeriod: Moment = 5;
-    }
+            let deposit = if Writers::<T>::contains_key(&sender, &writer) {
+                Zero::zero()
+            } else {
+                let deposit = T::WriterDeposit::get();
+                T::Currency::reserve(&sender, deposit)?;
+                DatalogWriterDeposit::<T>::insert(&sender, &writer, deposit);
+                deposit
+            };
 
-    impl pallet_timestamp::Trait for Runtime {
-        type Moment = Moment;
-        type OnTimestampSet = ();
-        type MinimumPeriod = ();
-        type WeightInfo = ();
-    }
+            let info = WriterInfo { expiry, min_interval, last_write: None };
+            Writers::<T>::insert(&sender, &writer, info);
+            Self::deposit_event(RawEvent::WriterAdded(sender.clone(), writer));
+            if !deposit.is_zero() {
+                Self::deposit_event(RawEvent::DepositReserved(sender, deposit));
+            }
+        }
 
-    const WINDOW: u64 = 20;
-    parameter_types! {
-        pub const WindowSize: u64 = WINDOW;
-        pub const MaximumMessageSize: usize = 512;
-    }
+        /// Remove permission of `writer` to record into sender`s datalog, returns writer deposit.
+        #[weight = <T as Trait>::WeightInfo::remove_writer()]
+        fn remove_writer(origin, writer: T::AccountId) {
+            let sender = ensure_signed(origin)?;
+            ensure!(Writers::<T>::contains_key(&sender, &writer), Error::<T>::NotAuthorized);
 
-    impl Trait for Runtime {
-        type Time = Timestamp;
-        type Record = Vec<u8>;
-        type Event = ();
-        type WindowSize = WindowSize;
-        type MaximumMessageSize = MaximumMessageSize;
-        type WeightInfo = ();
-    }
+            Writers::<T>::remove(&sender, &writer);
+            let deposit = DatalogWriterDeposit::<T>::take(&sender, &writer);
+            T::Currency::unreserve(&sender, deposit);
+            Self::deposit_event(RawEvent::WriterRemoved(sender.clone(), writer));
+            if !deposit.is_zero() {
+                Self::deposit_event(RawEvent::DepositReturned(sender, deposit));
+            }
+        }
 
-    fn new_test_ext() -> frame_support::sp_io::TestExternalities {
-        let storage = frame_system::GenesisConfig::default()
-            .build_storage::<Runtime>()
-            .unwrap();
-        storage.into()
-    }
+        /// Clear account`s datalog.
+        ///
+        /// Weight of the full window is charged upfront, weight of unused slots is refunded.
+        #[weight = <T as Trait>::WeightInfo::erase(T::MaximumWindowSize::get() as u32)]
+        fn erase(origin) -> DispatchResultWithPostInfo {
+            let sender = ensure_signed(origin)?;
+            Self::ensure_migrated()?;
 
-    #[test]
-    fn test_ringbuffer_index() {
-        let mut idx: RingBufferIndex = Default::default();
-        assert!(idx.start == idx.end);
-        assert!(idx.start == 0);
+            let (returned, erased) = Self::erase_all(&sender);
 
-        let i = idx.add(20);
-        assert!(i == 0);
-        assert!(idx.end == 1);
-    }
+            Self::deposit_event(RawEvent::Erased(sender.clone()));
+            if !returned.is_zero() {
+                Self::deposit_event(RawEvent::DepositReturned(sender, returned));
+            }
+            Ok(Some(<T as Trait>::WeightInfo::erase(erased)).into())
+        }
 
-    #[test]
-    fn test_store_data() {
-        new_test_ext().execute_with(|| {
-            let sender = 1;
-            let record = b"datalog".to_vec();
-            assert_ok!(Datalog::record(Origin::signed(sender), record.clone()));
-            assert_eq!(Datalog::data(&sender), vec![Item::new(0, record)]);
-        })
-    }
+        /// Clear datalog of any account, e.g. to remove illegal content.
+        ///
+        /// Deposits are returned to the account. Up to `archived` newest records are cleared
+        /// from offchain archive too, only their hashes remain in merkle mountain range leaves.
+        #[weight = <T as Trait>::WeightInfo::force_erase(
+            T::MaximumWindowSize::get() as u32,
+            *archived,
+        )]
+        fn force_erase(
+            origin,
+            account: T::AccountId,
+            archived: u32,
+        ) -> DispatchResultWithPostInfo {
+            T::ForceOrigin::ensure_origin(origin)?;
+            Self::ensure_migrated()?;
 
-    #[test]
-    fn test_recycle_data() {
-        new_test_ext().execute_with(|| {
-            let sender = 1;
+            let (returned, erased) = Self::erase_all(&account);
+            let cleared = Self::clear_archive(&account, archived);
 
-            for i in 0..(WINDOW + 10) {
-                assert_ok!(Datalog::record(
-                    Origin::signed(sender),
-                    i.to_be_bytes().to_vec()
-                ));
+            Self::deposit_event(RawEvent::ForceErased(account.clone()));
+            if !returned.is_zero() {
+                Self::deposit_event(RawEvent::DepositReturned(account, returned));
             }
+            Ok(Some(<T as Trait>::WeightInfo::force_erase(erased, cleared)).into())
+        }
 
-            let data: Vec<_> = (11..(WINDOW + 10))
-                .map(|i| Item::new(0, i.to_be_bytes().to_vec()))
-                .collect();
+        /// Forbid account to write records, e.g. when its key is compromised.
+        #[weight = <T as Trait>::WeightInfo::freeze()]
+        fn freeze(origin, account: T::AccountId) {
+            T::ForceOrigin::ensure_origin(origin)?;
 
-            assert_eq!(Datalog::data(&sender), data);
-            assert_eq!(
-                Datalog::datalogidx(&sender),
-                RingBufferIndex { start: 11, end: 10 }
-            );
-        })
-    }
+            Frozen::<T>::insert(&account, true);
+            Self::deposit_event(RawEvent::Frozen(account));
+        }
 
-    #[test]
-    fn test_erase_data() {
-        new_test_ext().execute_with(|| {
-            let sender = 1;
-            let record = b"datalog".to_vec();
-            assert_ok!(Datalog::record(Origin::signed(sender), record.clone()));
-            // old log should be empty
-            assert_eq!(Datalog::datalog(sender), vec![]);
-            assert_eq!(Datalog::data(&sender), vec![Item::new(0, record)]);
-            assert_eq!(
-                Datalog::datalogidx(&sender),
-                RingBufferIndex { start: 0, end: 1 }
-            );
+        /// Allow frozen account to write records again.
+        #[weight = <T as Trait>::WeightInfo::thaw()]
+        fn thaw(origin, account: T::AccountId) {
+            T::ForceOrigin::ensure_origin(origin)?;
+            ensure!(Frozen::<T>::get(&account), Error::<T>::NotFrozen);
 
-            assert_ok!(Datalog::erase(Origin::signed(sender)));
-            assert_eq!(Datalog::data(&sender), vec![]);
+            Frozen::<T>::remove(&account);
+            Self::deposit_event(RawEvent::Thawed(account));
+        }
 
-            assert_eq!(
-                Datalog::datalogidx(&sender),
-                RingBufferIndex { start: 0, end: 0 }
+        /// Erase single record of account`s datalog by its sequence number.
+        ///
+        /// Records on the shorter side of the erased one are moved to keep the buffer dense.
+        #[weight = <T as Trait>::WeightInfo::erase_item(T::MaximumWindowSize::get() as u32 / 2)]
+        fn erase_item(origin, seq: u64) -> DispatchResultWithPostInfo {
+            let sender = ensure_signed(origin)?;
+            Self::ensure_migrated()?;
+
+            let window_size = Self::window_size(&sender);
+            let mut idx = DatalogIndex::<T>::get(&sender);
+            let pos = Self::lower_bound(&sender, &idx, window_size, |item| item.seq() < seq);
+            ensure!(
+                pos < idx.len(window_size)
+                    && DatalogItem::<T>::get((&sender, idx.nth(pos, window_size))).seq() == seq,
+                Error::<T>::RecordNotFound
             );
-        })
+
+            let (returned, moved) = Self::remove_nth(&sender, &mut idx, window_size, pos);
+            DatalogIndex::<T>::insert(&sender, idx);
+
+            Self::deposit_event(RawEvent::RecordErased(sender.clone(), seq));
+            if !returned.is_zero() {
+                Self::deposit_event(RawEvent::DepositReturned(sender, returned));
+            }
+            Ok(Some(<T as Trait>::WeightInfo::erase_item(moved)).into())
+        }
+
+        /// Erase records of account`s datalog older than `moment`.
+        #[weight = <T as Trait>::WeightInfo::erase_older_than(T::MaximumWindowSize::get() as u32)]
+        fn erase_older_than(origin, moment: MomentOf<T>) -> DispatchResultWithPostInfo {
+            let sender = ensure_signed(origin)?;
+            Self::ensure_migrated()?;
+
+            let window_size = Self::window_size(&sender);
+            let mut idx = DatalogIndex::<T>::get(&sender);
+            let (returned, pruned) =
+                Self::prune(&sender, &mut idx, window_size, moment, window_size as u32);
+            DatalogIndex::<T>::insert(&sender, idx);
+
+            Self::deposit_event(RawEvent::ErasedOlderThan(sender.clone(), moment));
+            if !returned.is_zero() {
+                Self::deposit_event(RawEvent::DepositReturned(sender, returned));
+            }
+            Ok(Some(<T as Trait>::WeightInfo::erase_older_than(pruned)).into())
+        }
+
+        /// Set period after which records of account are dropped, `None` keeps them forever.
+        ///
+        /// Expired records are dropped lazily on new records, at most `MaxPrunePerRecord` at once.
+        /// Retention deposit is reserved while the period is set.
+        #[weight = <T as Trait>::WeightInfo::set_retention()]
+        fn set_retention(origin, period: Option<MomentOf<T>>) {
+            let sender = ensure_signed(origin)?;
+            let was_set = DatalogRetention::<T>::contains_key(&sender);
+
+            let (reserved, returned) = match period {
+                Some(period) => {
+                    let deposit = if was_set { Zero::zero() } else { T::RetentionDeposit::get() };
+                    T::Currency::reserve(&sender, deposit)?;
+                    if !was_set {
+                        DatalogRetentionDeposit::<T>::insert(&sender, deposit);
+                    }
+                    DatalogRetention::<T>::insert(&sender, period);
+                    (deposit, Zero::zero())
+                }
+                None => {
+                    let deposit = DatalogRetentionDeposit::<T>::take(&sender);
+                    T::Currency::unreserve(&sender, deposit);
+                    DatalogRetention::<T>::remove(&sender);
+                    (Zero::zero(), deposit)
+                }
+            };
+
+            Self::deposit_event(RawEvent::RetentionChanged(sender.clone(), period));
+            if !reserved.is_zero() {
+                Self::deposit_event(RawEvent::DepositReserved(sender.clone(), reserved));
+            }
+            if !returned.is_zero() {
+                Self::deposit_event(RawEvent::DepositReturned(sender, returned));
+            }
+        }
+
+        /// Send record into inbox of `recipient`.
+        ///
+        /// Record deposit is reserved from sender and returned when the record leaves the inbox.
+        #[weight = <T as Trait>::WeightInfo::send(record.size_hint() as u32)]
+        fn send(origin, recipient: T::AccountId, record: T::Record) {
+            ensure!(record.size_hint() <= T::MaximumMessageSize::get(), Error::<T>::RecordTooBig );
+            ensure!(record.is_valid(), Error::<T>::InvalidRecord );
+            let sender = ensure_signed(origin)?;
+            ensure!(!Frozen::<T>::get(&sender), Error::<T>::AccountFrozen);
+            ensure!(InboxSenders::<T>::get(&recipient, &sender), Error::<T>::SenderNotAllowed);
+
+            let deposit = Self::deposit_of(&record);
+            T::Currency::reserve(&sender, deposit)?;
+
+            let window_size = T::WindowSize::get();
+            let mut idx = InboxIndex::<T>::get(&recipient);
+            // the oldest item is dropped when inbox is full
+            if idx.len(window_size) + 1 >= window_size {
+                Self::remove_inbox_item(&recipient, idx.start);
+            }
+            let now = T::Time::now();
+            let end = idx.add(window_size);
+
+            InboxItems::<T>::insert((&recipient, end), InboxItem(sender.clone(), now, record.clone()));
+            InboxDeposit::<T>::insert((&recipient, end), deposit);
+            InboxIndex::<T>::insert(&recipient, idx);
+
+            Self::deposit_event(RawEvent::RecordSent(sender.clone(), recipient, now, record));
+            if !deposit.is_zero() {
+                Self::deposit_event(RawEvent::DepositReserved(sender, deposit));
+            }
+        }
+
+        /// Allow `sender` to send records into inbox of origin.
+        ///
+        /// Sender deposit is reserved from origin until the sender is disallowed.
+        #[weight = <T as Trait>::WeightInfo::allow_sender()]
+        fn allow_sender(origin, sender: T::AccountId) {
+            let recipient = ensure_signed(origin)?;
+            if InboxSenders::<T>::get(&recipient, &sender) {
+                return Ok(());
+            }
+
+            let deposit = T::SenderDeposit::get();
+            T::Currency::reserve(&recipient, deposit)?;
+            InboxSenderDeposit::<T>::insert(&recipient, &sender, deposit);
+            InboxSenders::<T>::insert(&recipient, &sender, true);
+
+            Self::deposit_event(RawEvent::SenderAllowed(recipient.clone(), sender));
+            if !deposit.is_zero() {
+                Self::deposit_event(RawEvent::DepositReserved(recipient, deposit));
+            }
+        }
+
+        /// Disallow `sender` to send records into inbox of origin, returns sender deposit.
+        #[weight = <T as Trait>::WeightInfo::disallow_sender()]
+        fn disallow_sender(origin, sender: T::AccountId) {
+            let recipient = ensure_signed(origin)?;
+            ensure!(InboxSenders::<T>::get(&recipient, &sender), Error::<T>::SenderNotAllowed);
+
+            InboxSenders::<T>::remove(&recipient, &sender);
+            let deposit = InboxSenderDeposit::<T>::take(&recipient, &sender);
+            T::Currency::unreserve(&recipient, deposit);
+            Self::deposit_event(RawEvent::SenderDisallowed(recipient.clone(), sender));
+            if !deposit.is_zero() {
+                Self::deposit_event(RawEvent::DepositReturned(recipient, deposit));
+            }
+        }
+
+        /// Clear inbox of origin, deposits are returned to senders.
+        #[weight = <T as Trait>::WeightInfo::clear_inbox(T::WindowSize::get() as u32)]
+        fn clear_inbox(origin) -> DispatchResultWithPostInfo {
+            let recipient = ensure_signed(origin)?;
+
+            let mut idx = InboxIndex::<T>::take(&recipient);
+            let mut cleared = 0u32;
+            for slot in idx.iter(T::WindowSize::get()) {
+                Self::remove_inbox_item(&recipient, slot);
+                cleared += 1;
+            }
+
+            Self::deposit_event(RawEvent::InboxCleared(recipient));
+            Ok(Some(<T as Trait>::WeightInfo::clear_inbox(cleared)).into())
+        }
+
+        /// Change window size of account`s datalog.
+        ///
+        /// Records not fitting into the new window are dropped, oldest first.
+        /// Weight of moving the full window is charged upfront, weight of absent records is refunded.
+        #[weight = <T as Trait>::WeightInfo::set_window_size(T::MaximumWindowSize::get() as u32)]
+        fn set_window_size(origin, window_size: u64) -> DispatchResultWithPostInfo {
+            let sender = ensure_signed(origin)?;
+            ensure!(
+                window_size > 1 && window_size <= T::MaximumWindowSize::get(),
+                Error::<T>::BadWindowSize
+            );
+            Self::ensure_migrated()?;
+
+            let (items, total) = Self::take_items(&sender, Self::window_size(&sender));
+            let moved = items.len() as u32;
+            DatalogWindow::<T>::insert(&sender, window_size);
+            let returned = Self::put_items(&sender, items, window_size, total);
+
+            Self::deposit_event(RawEvent::WindowSizeChanged(sender.clone(), window_size));
+            if !returned.is_zero() {
+                Self::deposit_event(RawEvent::DepositReturned(sender, returned));
+            }
+            Ok(Some(<T as Trait>::WeightInfo::set_window_size(moved)).into())
+        }
+    }
+}
+
+impl<T: Trait> Module<T> {
+    /// Deposit required to store given record.
+    pub fn deposit_of(record: &T::Record) -> BalanceOf<T> {
+        let bytes = record.encoded_size() as u32;
+        T::DepositPerByte::get()
+            .saturating_mul(bytes.into())
+            .saturating_add(T::DepositPerItem::get())
+    }
+
+    /// Fail while datalog storage is migrated to the current layout.
+    fn ensure_migrated() -> DispatchResult {
+        ensure!(StorageVersion::get() == Releases::V2, Error::<T>::Migrating);
+        Ok(())
+    }
+
+    /// Window size of account`s datalog.
+    pub fn window_size(account: &T::AccountId) -> u64 {
+        DatalogWindow::<T>::get(account).unwrap_or_else(T::WindowSize::get)
+    }
+
+    /// Reserve record deposit and push record into account`s ring buffer.
+    ///
+    /// Returns sequence number of the record.
+    fn insert_record(account: T::AccountId, record: T::Record) -> Result<u64, DispatchError> {
+        let deposit = Self::deposit_of(&record);
+        T::Currency::reserve(&account, deposit)?;
+
+        let (item, returned) = Self::append(&account, record, deposit);
+        let block = <frame_system::Module<T>>::block_number();
+        let seq = item.seq();
+        let (now, record) = item.into();
+        Self::deposit_event(RawEvent::NewRecord(account.clone(), now, record, seq, block));
+        if !deposit.is_zero() {
+            Self::deposit_event(RawEvent::DepositReserved(account.clone(), deposit));
+        }
+        if !returned.is_zero() {
+            Self::deposit_event(RawEvent::DepositReturned(account, returned));
+        }
+        Ok(seq)
+    }
+
+    /// Append record with already reserved deposit to account`s datalog.
+    ///
+    /// Returns appended item and deposit of dropped records.
+    fn append(
+        account: &T::AccountId,
+        record: T::Record,
+        deposit: BalanceOf<T>,
+    ) -> (RingBufferItem<T>, BalanceOf<T>) {
+        let now = T::Time::now();
+        let window_size = Self::window_size(&account);
+        let mut idx = DatalogIndex::<T>::get(&account);
+        let mut returned = match DatalogRetention::<T>::get(&account) {
+            Some(period) => {
+                let expired = now.saturating_sub(period);
+                let max = T::MaxPrunePerRecord::get();
+                Self::prune(&account, &mut idx, window_size, expired, max).0
+            }
+            None => Zero::zero(),
+        };
+        // the oldest item is dropped when buffer is full
+        if idx.len(window_size) + 1 >= window_size {
+            returned = returned.saturating_add(Self::remove_item(&account, idx.start));
+        }
+        let block = <frame_system::Module<T>>::block_number();
+        let item = RingBufferItem(now, record, idx.total, Some(block));
+        let end = idx.add(window_size);
+
+        DatalogItem::<T>::insert((&account, end), &item);
+        DatalogDeposit::<T>::insert((&account, end), deposit);
+        DatalogIndex::<T>::insert(&account, idx);
+        Self::commit_history(&account, &item);
+        (item, returned)
+    }
+
+    /// Remove all records of account keeping its sequence numbers.
+    ///
+    /// Returns unreserved deposit and number of removed records.
+    fn erase_all(account: &T::AccountId) -> (BalanceOf<T>, u32) {
+        let mut idx = DatalogIndex::<T>::take(account);
+        let window_size = Self::window_size(account);
+        let mut returned: BalanceOf<T> = Zero::zero();
+        let mut erased = 0u32;
+        for slot in idx.iter(window_size) {
+            returned = returned.saturating_add(Self::remove_item(account, slot));
+            erased += 1;
+        }
+        // sequence numbers continue after erase
+        let idx = RingBufferIndex { total: idx.total, ..Default::default() };
+        DatalogIndex::<T>::insert(account, idx);
+        (returned, erased)
+    }
+
+    /// Clear at most `count` newest records of account from offchain archive.
+    ///
+    /// Returns number of cleared records.
+    fn clear_archive(account: &T::AccountId, count: u32) -> u32 {
+        let total = DatalogIndex::<T>::get(account).total;
+        let from = total.saturating_sub(count as u64);
+        for seq in from..total {
+            frame_support::sp_io::offchain_index::clear(&archive_key(account, seq));
+        }
+        (total - from) as u32
+    }
+
+    /// Drop at most `max` oldest records older than `moment`.
+    ///
+    /// Returns unreserved deposit and number of dropped records.
+    fn prune(
+        account: &T::AccountId,
+        idx: &mut RingBufferIndex,
+        window_size: u64,
+        moment: MomentOf<T>,
+        max: u32,
+    ) -> (BalanceOf<T>, u32) {
+        let mut returned: BalanceOf<T> = Zero::zero();
+        let mut pruned = 0;
+        while pruned < max
+            && !idx.is_empty()
+            && DatalogItem::<T>::get((account, idx.start)).0 < moment
+        {
+            returned = returned.saturating_add(Self::remove_item(account, idx.start));
+            RingBufferIndex::next(&mut idx.start, window_size);
+            pruned += 1;
+        }
+        (returned, pruned)
+    }
+
+    /// Remove `pos`-th record moving records on the shorter side of it.
+    ///
+    /// Returns unreserved deposit and number of moved records.
+    fn remove_nth(
+        account: &T::AccountId,
+        idx: &mut RingBufferIndex,
+        window_size: u64,
+        pos: u64,
+    ) -> (BalanceOf<T>, u32) {
+        let len = idx.len(window_size);
+        let returned = Self::remove_item(account, idx.nth(pos, window_size));
+        let moved = if pos < len / 2 {
+            for n in (0..pos).rev() {
+                Self::move_item(account, idx.nth(n, window_size), idx.nth(n + 1, window_size));
+            }
+            RingBufferIndex::next(&mut idx.start, window_size);
+            pos
+        } else {
+            for n in (pos + 1)..len {
+                Self::move_item(account, idx.nth(n, window_size), idx.nth(n - 1, window_size));
+            }
+            idx.end = idx.nth(len - 1, window_size);
+            len - pos - 1
+        };
+        (returned, moved as u32)
+    }
+
+    /// Move ringbuffer item with its deposit into another slot.
+    fn move_item(account: &T::AccountId, from: u64, to: u64) {
+        DatalogItem::<T>::insert((account, to), DatalogItem::<T>::take((account, from)));
+        DatalogDeposit::<T>::insert((account, to), DatalogDeposit::<T>::take((account, from)));
+    }
+
+    /// Commit record into merkle mountain range and offchain archive of account.
+    ///
+    /// Archive is not fork aware, see [`archive_key`].
+    fn commit_history(account: &T::AccountId, item: &RingBufferItem<T>) {
+        let leaf = mmr::leaf_hash::<T::Hashing, _, _>(&item.0, &item.1);
+        let (index, nodes) = Self::mmr_push(account, leaf);
+        // full history and range nodes are kept by nodes with offchain indexing enabled,
+        // inclusion proofs are built from them off-chain
+        frame_support::sp_io::offchain_index::set(
+            &archive_key(account, item.seq()),
+            &(&item.0, &item.1).encode(),
+        );
+        let first = mmr::leaf_pos(index);
+        for (pos, node) in (first..).zip(nodes) {
+            frame_support::sp_io::offchain_index::set(&mmr_node_key(account, pos), &node.encode());
+        }
+    }
+
+    /// Append leaf to merkle mountain range of account and update its root,
+    /// returns index of the leaf and appended nodes.
+    ///
+    /// Only peaks of the range are kept in state, so it grows logarithmically.
+    fn mmr_push(account: &T::AccountId, leaf: T::Hash) -> (u64, Vec<T::Hash>) {
+        let index = DatalogMmrLeaves::<T>::get(account);
+        let mut peaks = DatalogMmrPeaks::<T>::get(account);
+        let nodes = mmr::push::<T::Hashing>(&mut peaks, index, leaf);
+
+        DatalogMmrLeaves::<T>::insert(account, index + 1);
+        DatalogMmrRoot::<T>::insert(account, mmr::root::<T::Hashing>(&peaks));
+        DatalogMmrPeaks::<T>::insert(account, peaks);
+        (index, nodes)
+    }
+
+    /// Return deposit to account, returns amount unreserved.
+    fn return_deposit(account: &T::AccountId, deposit: BalanceOf<T>) -> BalanceOf<T> {
+        deposit.saturating_sub(T::Currency::unreserve(account, deposit))
+    }
+
+    /// Remove inbox item and return deposit to its sender.
+    fn remove_inbox_item(account: &T::AccountId, slot: u64) {
+        let InboxItem(sender, _, _) = InboxItems::<T>::take((account, slot));
+        let deposit = InboxDeposit::<T>::take((account, slot));
+        let returned = Self::return_deposit(&sender, deposit);
+        if !returned.is_zero() {
+            Self::deposit_event(RawEvent::DepositReturned(sender, returned));
+        }
+    }
+
+    /// Records sent into inbox of account, oldest first.
+    pub fn inbox(account: &T::AccountId) -> Vec<InboxItem<T>> {
+        let mut idx = InboxIndex::<T>::get(account);
+        idx.iter(T::WindowSize::get())
+            .map(|i| InboxItems::<T>::get((account, i)))
+            .collect()
+    }
+
+    /// Remove ringbuffer item and return its deposit, returns amount unreserved.
+    fn remove_item(account: &T::AccountId, slot: u64) -> BalanceOf<T> {
+        let item = DatalogItem::<T>::take((account, slot));
+        DatalogAttestation::<T>::remove((account, item.seq()));
+        Self::return_deposit(account, DatalogDeposit::<T>::take((account, slot)))
+    }
+
+    /// Take all ringbuffer items of account with their deposits, oldest first,
+    /// and total counter of its index.
+    fn take_items(
+        account: &T::AccountId,
+        window_size: u64,
+    ) -> (Vec<(RingBufferItem<T>, BalanceOf<T>)>, u64) {
+        let mut idx = DatalogIndex::<T>::take(account);
+        let items = idx
+            .iter(window_size)
+            .map(|i| {
+                (
+                    DatalogItem::<T>::take((account, i)),
+                    DatalogDeposit::<T>::take((account, i)),
+                )
+            })
+            .collect();
+        (items, idx.total)
+    }
+
+    /// Put items into empty ringbuffer of account, oldest first.
+    ///
+    /// Items not fitting into the window are dropped, returns their unreserved deposit.
+    fn put_items(
+        account: &T::AccountId,
+        mut items: Vec<(RingBufferItem<T>, BalanceOf<T>)>,
+        window_size: u64,
+        total: u64,
+    ) -> BalanceOf<T> {
+        let capacity = window_size.saturating_sub(1) as usize;
+        let skip = items.len().saturating_sub(capacity);
+        let returned = items
+            .drain(..skip)
+            .fold(Zero::zero(), |returned: BalanceOf<T>, (item, deposit)| {
+                DatalogAttestation::<T>::remove((account, item.seq()));
+                returned.saturating_add(Self::return_deposit(account, deposit))
+            });
+
+        let mut idx = RingBufferIndex::default();
+        for (item, deposit) in items {
+            let end = idx.add(window_size);
+            DatalogItem::<T>::insert((account, end), item);
+            DatalogDeposit::<T>::insert((account, end), deposit);
+        }
+        idx.total = total;
+        DatalogIndex::<T>::insert(account, idx);
+
+        returned
+    }
+
+    pub fn data(account: &T::AccountId) -> Vec<RingBufferItem<T>> {
+        let mut idx = DatalogIndex::<T>::get(&account);
+        let window_size = Self::window_size(account);
+
+        idx.iter(window_size)
+            .map(|i| DatalogItem::<T>::get((&account, i)))
+            .collect()
+    }
+
+    /// Records of account with their attestation status, oldest first.
+    pub fn entries(account: &T::AccountId) -> Vec<Entry<T::AccountId, MomentOf<T>, T::Record>> {
+        Self::data(account)
+            .into_iter()
+            .map(|item| {
+                let seq = item.seq();
+                let (moment, record) = item.into();
+                Entry {
+                    seq,
+                    moment,
+                    record,
+                    attested_by: DatalogAttestation::<T>::get((account, seq)),
+                }
+            })
+            .collect()
+    }
+
+    /// Block the record with given sequence number was written in.
+    ///
+    /// Returns `None` when the record is no longer stored or predates block tagging.
+    pub fn block_of(account: &T::AccountId, seq: u64) -> Option<T::BlockNumber> {
+        let window_size = Self::window_size(account);
+        let idx = DatalogIndex::<T>::get(account);
+        let pos = Self::lower_bound(account, &idx, window_size, |item| item.seq() < seq);
+        if pos < idx.len(window_size) {
+            let item = DatalogItem::<T>::get((account, idx.nth(pos, window_size)));
+            item.block().filter(|_| item.seq() == seq)
+        } else {
+            None
+        }
+    }
+
+    /// Records of account with timestamp in `[from, to]` range.
+    pub fn data_range(
+        account: &T::AccountId,
+        from: MomentOf<T>,
+        to: MomentOf<T>,
+    ) -> Vec<RingBufferItem<T>> {
+        let idx = DatalogIndex::<T>::get(&account);
+        let window_size = Self::window_size(account);
+
+        let first = Self::lower_bound(account, &idx, window_size, |item| item.0 < from);
+        (first..idx.len(window_size))
+            .map(|n| DatalogItem::<T>::get((&account, idx.nth(n, window_size))))
+            .take_while(|item| item.0 <= to)
+            .collect()
+    }
+
+    /// Last `count` records of account, oldest first.
+    pub fn data_last(account: &T::AccountId, count: u64) -> Vec<RingBufferItem<T>> {
+        let idx = DatalogIndex::<T>::get(&account);
+        let window_size = Self::window_size(account);
+
+        let len = idx.len(window_size);
+        (len.saturating_sub(count)..len)
+            .map(|n| DatalogItem::<T>::get((&account, idx.nth(n, window_size))))
+            .collect()
+    }
+
+    /// Page of at most `limit` records starting from `cursor` (0 is the oldest record).
+    ///
+    /// Returns the records and the cursor of the next page, if there is one.
+    pub fn data_page(
+        account: &T::AccountId,
+        cursor: u64,
+        limit: u64,
+    ) -> (Vec<RingBufferItem<T>>, Option<u64>) {
+        let idx = DatalogIndex::<T>::get(&account);
+        let window_size = Self::window_size(account);
+
+        let len = idx.len(window_size);
+        let end = cursor.saturating_add(limit).min(len);
+        let page = (cursor.min(end)..end)
+            .map(|n| DatalogItem::<T>::get((&account, idx.nth(n, window_size))))
+            .collect();
+        let next = if end < len { Some(end) } else { None };
+        (page, next)
+    }
+
+    /// Position of the first record for which `before` is false.
+    ///
+    /// Records are appended with non-decreasing timestamps and sequence numbers,
+    /// so binary search decodes only a logarithmic number of items.
+    fn lower_bound(
+        account: &T::AccountId,
+        idx: &RingBufferIndex,
+        window_size: u64,
+        before: impl Fn(&RingBufferItem<T>) -> bool,
+    ) -> u64 {
+        let (mut low, mut high) = (0, idx.len(window_size));
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if before(&DatalogItem::<T>::get((&account, idx.nth(mid, window_size)))) {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        low
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use base58::FromBase58;
+    use frame_support::sp_runtime::{
+        testing::{Header, TestSignature, UintAuthorityId},
+        traits::{BlakeTwo256, IdentityLookup},
+        DispatchError, Perbill,
+    };
+    use frame_support::{
+        assert_err, assert_noop, assert_ok, impl_outer_event, impl_outer_origin, parameter_types,
+        storage::unhashed,
+        traits::OnInitialize,
+        weights::{
+            constants::{BlockExecutionWeight, ExtrinsicBaseWeight},
+            Weight,
+        },
+        StorageMap, StorageValue,
+    };
+    use node_primitives::Moment;
+    use sp_core::{
+        offchain::{OffchainStorage, STORAGE_PREFIX},
+        H256,
+    };
+
+    impl_outer_origin! {
+        pub enum Origin for Runtime {}
+    }
+
+    mod datalog {
+        pub use crate::Event;
+    }
+
+    impl_outer_event! {
+        pub enum TestEvent for Runtime {
+            frame_system<T>,
+            pallet_balances<T>,
+            datalog<T>,
+        }
+    }
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    pub struct Runtime;
+    type Datalog = Module<Runtime>;
+    type System = frame_system::Module<Runtime>;
+    type RuntimeError = Error<Runtime>;
+    type Timestamp = pallet_timestamp::Module<Runtime>;
+    type Balances = pallet_balances::Module<Runtime>;
+    type Item = RingBufferItem<Runtime>;
+
+    parameter_types! {
+        pub const BlockHashCount: u64 = 250;
+
+        pub const MaximumBlockWeight: Weight = 2_000_000_000;
+        pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
+
+        pub MaximumExtrinsicWeight: Weight = 1_000_000_000;
+        pub const MaximumBlockLength: u32 = 1_000_000;
+    }
+
+    impl frame_system::Trait for Runtime {
+        type Origin = Origin;
+        type Index = u64;
+        type BlockNumber = u64;
+        type Call = ();
+        type Hash = H256;
+        type Hashing = BlakeTwo256;
+        type AccountId = u64;
+        type Lookup = IdentityLookup<Self::AccountId>;
+        type Header = Header;
+        type Event = TestEvent;
+        type BlockHashCount = BlockHashCount;
+        type Version = ();
+        type PalletInfo = ();
+        type AccountData = pallet_balances::AccountData<u64>;
+        type OnNewAccount = ();
+        type OnKilledAccount = ();
+        type DbWeight = ();
+        type BaseCallFilter = ();
+        type SystemWeightInfo = ();
+        type BlockExecutionWeight = BlockExecutionWeight;
+        type ExtrinsicBaseWeight = ExtrinsicBaseWeight;
+        type MaximumExtrinsicWeight = MaximumExtrinsicWeight;
+        type MaximumBlockWeight = MaximumBlockWeight;
+        type MaximumBlockLength = MaximumBlockLength;
+        type AvailableBlockRatio = AvailableBlockRatio;
+    }
+
+    parameter_types! {
+        pub const MinimumPeriod: Moment = 5;
+    }
+
+    impl pallet_timestamp::Trait for Runtime {
+        type Moment = Moment;
+        type OnTimestampSet = ();
+        type MinimumPeriod = ();
+        type WeightInfo = ();
+    }
+
+    parameter_types! {
+        pub const ExistentialDeposit: u64 = 1;
+    }
+
+    impl pallet_balances::Trait for Runtime {
+        type MaxLocks = ();
+        type Balance = u64;
+        type DustRemoval = ();
+        type Event = TestEvent;
+        type ExistentialDeposit = ExistentialDeposit;
+        type AccountStore = frame_system::Module<Runtime>;
+        type WeightInfo = ();
+    }
+
+    const WINDOW: u64 = 20;
+    const DEPOSIT_PER_ITEM: u64 = 10;
+    const WRITER_DEPOSIT: u64 = 5;
+    const SENDER_DEPOSIT: u64 = 3;
+    const RETENTION_DEPOSIT: u64 = 7;
+    const DEVICE_DEPOSIT: u64 = 20;
+    parameter_types! {
+        pub const WindowSize: u64 = WINDOW;
+        pub const MaximumWindowSize: u64 = 100;
+        pub const MaximumMessageSize: usize = 512;
+        pub const DepositPerItem: u64 = DEPOSIT_PER_ITEM;
+        pub const DepositPerByte: u64 = 1;
+        pub const WriterDeposit: u64 = WRITER_DEPOSIT;
+        pub const SenderDeposit: u64 = SENDER_DEPOSIT;
+        pub const RetentionDeposit: u64 = RETENTION_DEPOSIT;
+        pub const DeviceDeposit: u64 = DEVICE_DEPOSIT;
+        pub const MaxPrunePerRecord: u32 = 2;
+        pub const MaxBatchLength: u32 = 5;
+        pub const BatchRecordEvents: bool = false;
+        pub const MaxMigratedPerBlock: u32 = 2;
+    }
+
+    impl Trait for Runtime {
+        type Time = Timestamp;
+        type Record = Vec<u8>;
+        type Event = TestEvent;
+        type WindowSize = WindowSize;
+        type MaximumWindowSize = MaximumWindowSize;
+        type MaximumMessageSize = MaximumMessageSize;
+        type Currency = Balances;
+        type DepositPerItem = DepositPerItem;
+        type DepositPerByte = DepositPerByte;
+        type WriterDeposit = WriterDeposit;
+        type SenderDeposit = SenderDeposit;
+        type RetentionDeposit = RetentionDeposit;
+        type DeviceDeposit = DeviceDeposit;
+        type MaxPrunePerRecord = MaxPrunePerRecord;
+        type MaxBatchLength = MaxBatchLength;
+        type BatchRecordEvents = BatchRecordEvents;
+        type MaxMigratedPerBlock = MaxMigratedPerBlock;
+        type ForceOrigin = frame_system::EnsureRoot<u64>;
+        type Signature = TestSignature;
+        type Public = UintAuthorityId;
+        #[cfg(feature = "runtime-benchmarks")]
+        type DeviceKeys = TestDeviceKeys;
+        type WeightInfo = ();
+    }
+
+    /// Device keys of benchmarks, test signature is the signed payload itself.
+    #[cfg(feature = "runtime-benchmarks")]
+    pub struct TestDeviceKeys;
+
+    #[cfg(feature = "runtime-benchmarks")]
+    impl DeviceKeys<u64, TestSignature> for TestDeviceKeys {
+        fn generate() -> u64 {
+            10
+        }
+
+        fn sign(device: &u64, payload: &[u8]) -> TestSignature {
+            TestSignature(*device, payload.to_vec())
+        }
+    }
+
+    pub(crate) fn new_test_ext() -> frame_support::sp_io::TestExternalities {
+        let mut storage = frame_system::GenesisConfig::default()
+            .build_storage::<Runtime>()
+            .unwrap();
+        pallet_balances::GenesisConfig::<Runtime> {
+            balances: vec![(1, 1_000_000), (2, 1_000_000), (3, 1_000_000)],
+        }
+        .assimilate_storage(&mut storage)
+        .unwrap();
+        GenesisConfig::default()
+            .assimilate_storage::<Runtime>(&mut storage)
+            .unwrap();
+        storage.into()
+    }
+
+    /// The latest `n` datalog events, events are deposited since the first block.
+    fn last_events(n: usize) -> Vec<Event<Runtime>> {
+        let events: Vec<_> = System::events()
+            .into_iter()
+            .filter_map(|record| match record.event {
+                TestEvent::datalog(event) => Some(event),
+                _ => None,
+            })
+            .collect();
+        events[events.len().saturating_sub(n)..].to_vec()
+    }
+
+    #[test]
+    fn test_ringbuffer_index() {
+        let mut idx: RingBufferIndex = Default::default();
+        assert!(idx.start == idx.end);
+        assert!(idx.start == 0);
+
+        let i = idx.add(20);
+        assert!(i == 0);
+        assert!(idx.end == 1);
+    }
+
+    #[test]
+    fn test_store_data() {
+        new_test_ext().execute_with(|| {
+            let sender = 1;
+            let record = b"datalog".to_vec();
+            System::set_block_number(1);
+            assert_ok!(Datalog::record(Origin::signed(sender), record.clone()));
+            assert_eq!(Datalog::data(&sender), vec![Item::new(0, record.clone(), 0)]);
+            assert_eq!(
+                last_events(2),
+                vec![
+                    RawEvent::NewRecord(sender, 0, record.clone(), 0, 1),
+                    RawEvent::DepositReserved(sender, Datalog::deposit_of(&record)),
+                ]
+            );
+        })
+    }
+
+    #[test]
+    fn test_recycle_data() {
+        new_test_ext().execute_with(|| {
+            let sender = 1;
+
+            for i in 0..(WINDOW + 10) {
+                assert_ok!(Datalog::record(
+                    Origin::signed(sender),
+                    i.to_be_bytes().to_vec()
+                ));
+            }
+
+            let data: Vec<_> = (11..(WINDOW + 10))
+                .map(|i| Item::new(0, i.to_be_bytes().to_vec(), i))
+                .collect();
+
+            assert_eq!(Datalog::data(&sender), data);
+            assert_eq!(
+                Datalog::datalogidx(&sender),
+                RingBufferIndex { start: 11, end: 10, total: WINDOW + 10 }
+            );
+        })
+    }
+
+    #[test]
+    fn test_erase_data() {
+        new_test_ext().execute_with(|| {
+            let sender = 1;
+            let record = b"datalog".to_vec();
+            assert_ok!(Datalog::record(Origin::signed(sender), record.clone()));
+            // old log should be empty
+            assert_eq!(Datalog::datalog(sender), vec![]);
+            assert_eq!(Datalog::data(&sender), vec![Item::new(0, record.clone(), 0)]);
+            assert_eq!(
+                Datalog::datalogidx(&sender),
+                RingBufferIndex { start: 0, end: 1, total: 1 }
+            );
+
+            assert_ok!(Datalog::erase(Origin::signed(sender)));
+            assert_eq!(Datalog::data(&sender), vec![]);
+
+            assert_eq!(
+                Datalog::datalogidx(&sender),
+                RingBufferIndex { start: 0, end: 0, total: 1 }
+            );
+
+            // sequence numbers continue after erase
+            assert_ok!(Datalog::record(Origin::signed(sender), record.clone()));
+            assert_eq!(Datalog::data(&sender), vec![Item::new(0, record, 1)]);
+        })
+    }
+
+    #[test]
+    fn test_erase_refund() {
+        new_test_ext().execute_with(|| {
+            let sender = 1;
+            for i in 0..3u64 {
+                assert_ok!(Datalog::record(Origin::signed(sender), i.to_be_bytes().to_vec()));
+            }
+
+            let info = Datalog::erase(Origin::signed(sender)).unwrap();
+            assert_eq!(info.actual_weight, Some(<() as WeightInfo>::erase(3)));
+            let max = MaximumWindowSize::get() as u32;
+            assert!(<() as WeightInfo>::erase(3) < <() as WeightInfo>::erase(max));
+        })
+    }
+
+    #[test]
+    fn test_record_for() {
+        new_test_ext().execute_with(|| {
+            let owner = 1;
+            let writer = 2;
+            let record = b"datalog".to_vec();
+            System::set_block_number(1);
+            assert_noop!(
+                Datalog::record_for(Origin::signed(writer), owner, record.clone()),
+                RuntimeError::NotAuthorized
+            );
+
+            assert_ok!(Datalog::add_writer(Origin::signed(owner), writer, None, 0));
+            assert_eq!(
+                last_events(2),
+                vec![
+                    RawEvent::WriterAdded(owner, writer),
+                    RawEvent::DepositReserved(owner, WRITER_DEPOSIT),
+                ]
+            );
+            assert_eq!(Balances::reserved_balance(owner), WRITER_DEPOSIT);
+            assert_ok!(Datalog::record_for(Origin::signed(writer), owner, record.clone()));
+            assert_eq!(
+                last_events(2),
+                vec![
+                    RawEvent::NewRecord(owner, 0, record.clone(), 0, 1),
+                    RawEvent::DepositReserved(owner, Datalog::deposit_of(&record)),
+                ]
+            );
+            assert_eq!(Datalog::data(&owner), vec![Item::new(0, record.clone(), 0)]);
+            assert_eq!(Datalog::data(&writer), vec![]);
+            assert_eq!(
+                Balances::reserved_balance(owner),
+                WRITER_DEPOSIT + Datalog::deposit_of(&record)
+            );
+            assert_eq!(Balances::reserved_balance(writer), 0);
+
+            // owner semantics are untouched
+            assert_ok!(Datalog::erase(Origin::signed(owner)));
+            assert_eq!(Datalog::data(&owner), vec![]);
+            assert_eq!(Balances::reserved_balance(owner), WRITER_DEPOSIT);
+        })
+    }
+
+    #[test]
+    fn test_writer_limits() {
+        new_test_ext().execute_with(|| {
+            let owner = 1;
+            let writer = 2;
+            let record = b"datalog".to_vec();
+            System::set_block_number(1);
+            assert_ok!(Datalog::add_writer(Origin::signed(owner), writer, Some(10), 5));
+
+            assert_ok!(Datalog::record_for(Origin::signed(writer), owner, record.clone()));
+            System::set_block_number(5);
+            assert_noop!(
+                Datalog::record_for(Origin::signed(writer), owner, record.clone()),
+                RuntimeError::TooFrequent
+            );
+            System::set_block_number(6);
+            assert_ok!(Datalog::record_for(Origin::signed(writer), owner, record.clone()));
+
+            System::set_block_number(11);
+            assert_noop!(
+                Datalog::record_for(Origin::signed(writer), owner, record),
+                RuntimeError::WriterExpired
+            );
+        })
+    }
+
+    #[test]
+    fn test_record_for_failed_deposit() {
+        new_test_ext().execute_with(|| {
+            // owner can't reserve record deposit
+            let (owner, writer) = (4, 2);
+            System::set_block_number(1);
+            assert_noop!(
+                Datalog::add_writer(Origin::signed(owner), writer, None, 5),
+                pallet_balances::Error::<Runtime>::InsufficientBalance
+            );
+            assert_ok!(Balances::set_balance(Origin::root(), owner, WRITER_DEPOSIT, 0));
+            assert_ok!(Datalog::add_writer(Origin::signed(owner), writer, None, 5));
+
+            assert_noop!(
+                Datalog::record_for(Origin::signed(writer), owner, b"datalog".to_vec()),
+                pallet_balances::Error::<Runtime>::InsufficientBalance
+            );
+            assert_eq!(Datalog::writers(owner, writer).unwrap().last_write, None);
+        })
+    }
+
+    #[test]
+    fn test_remove_writer() {
+        new_test_ext().execute_with(|| {
+            let owner = 1;
+            let writer = 2;
+            assert_noop!(
+                Datalog::remove_writer(Origin::signed(owner), writer),
+                RuntimeError::NotAuthorized
+            );
+
+            assert_ok!(Datalog::add_writer(Origin::signed(owner), writer, None, 0));
+            assert!(Datalog::writers(owner, writer).is_some());
+            // replaced permission keeps its deposit
+            assert_ok!(Datalog::add_writer(Origin::signed(owner), writer, Some(10), 0));
+            assert_eq!(Balances::reserved_balance(owner), WRITER_DEPOSIT);
+            System::set_block_number(1);
+            assert_ok!(Datalog::remove_writer(Origin::signed(owner), writer));
+            assert_eq!(Datalog::writers(owner, writer), None);
+            assert_eq!(
+                last_events(2),
+                vec![
+                    RawEvent::WriterRemoved(owner, writer),
+                    RawEvent::DepositReturned(owner, WRITER_DEPOSIT),
+                ]
+            );
+            assert_eq!(Balances::reserved_balance(owner), 0);
+            assert_noop!(
+                Datalog::record_for(Origin::signed(writer), owner, b"datalog".to_vec()),
+                RuntimeError::NotAuthorized
+            );
+        })
+    }
+
+    #[test]
+    fn test_mmr_positions() {
+        assert_eq!((0..5).map(mmr::leaf_pos).collect::<Vec<_>>(), vec![0, 1, 3, 4, 7]);
+        assert_eq!(mmr::size(4), 7);
+        assert_eq!(mmr::size(7), 11);
+        assert_eq!(mmr::peaks(7), vec![(6, 2, 0), (9, 1, 4), (10, 0, 6)]);
+        assert_eq!(mmr::proof_positions(5, 7), Some((vec![7], 1)));
+        assert_eq!(mmr::proof_positions(7, 7), None);
+    }
+
+    #[test]
+    fn test_mmr_proof() {
+        new_test_ext().execute_with(|| {
+            let sender = 1;
+            // ring buffer is overwritten but history is still provable
+            for i in 0..(2 * WINDOW + 3) {
+                Timestamp::set_timestamp(i);
+                assert_ok!(Datalog::record(Origin::signed(sender), i.to_be_bytes().to_vec()));
+            }
+            assert_eq!(Datalog::mmr_leaves(sender), 2 * WINDOW + 3);
+            // only peaks are kept on-chain
+            assert_eq!(Datalog::mmr_peaks(sender).len(), mmr::peaks(2 * WINDOW + 3).len());
+
+            let leaves: Vec<_> = (0..(2 * WINDOW + 3))
+                .map(|i| mmr::leaf_hash::<BlakeTwo256, _, _>(&i, &i.to_be_bytes().to_vec()))
+                .collect();
+            assert_eq!(mmr::build_proof::<BlakeTwo256>(&leaves, 2 * WINDOW + 3), None);
+
+            let root = Datalog::mmr_root(sender);
+            for (i, leaf) in leaves.iter().enumerate() {
+                let proof = mmr::build_proof::<BlakeTwo256>(&leaves, i as u64).unwrap();
+                assert_eq!(proof.peaks, Datalog::mmr_peaks(sender));
+                assert!(mmr::verify_proof::<BlakeTwo256>(&root, leaf, &proof));
+
+                let forged =
+                    mmr::leaf_hash::<BlakeTwo256, _, _>(&(i as u64), &b"forged".to_vec());
+                assert!(!mmr::verify_proof::<BlakeTwo256>(&root, &forged, &proof));
+            }
+
+            // proof is bound to the root it was made for
+            let proof = mmr::build_proof::<BlakeTwo256>(&leaves, 0).unwrap();
+            let leaf = leaves[0];
+            assert_ok!(Datalog::record(Origin::signed(sender), b"datalog".to_vec()));
+            assert!(!mmr::verify_proof::<BlakeTwo256>(&Datalog::mmr_root(sender), &leaf, &proof));
+        })
+    }
+
+    #[test]
+    fn test_erase_item() {
+        new_test_ext().execute_with(|| {
+            let sender = 1;
+            System::set_block_number(1);
+            store_timed_data(sender, WINDOW + 5);
+            let data = |skip: &[u64]| {
+                (6..(WINDOW + 5))
+                    .filter(|i| !skip.contains(i))
+                    .map(timed_item)
+                    .collect::<Vec<_>>()
+            };
+
+            // older side is moved
+            assert_ok!(Datalog::erase_item(Origin::signed(sender), 8));
+            assert_eq!(Datalog::data(&sender), data(&[8]));
+            assert_eq!(
+                last_events(2),
+                vec![
+                    RawEvent::RecordErased(sender, 8),
+                    RawEvent::DepositReturned(sender, DEPOSIT_PER_ITEM + 9),
+                ]
+            );
+            // newer side is moved
+            let info = Datalog::erase_item(Origin::signed(sender), WINDOW + 2).unwrap();
+            assert_eq!(info.actual_weight, Some(<() as WeightInfo>::erase_item(2)));
+            assert_eq!(Datalog::data(&sender), data(&[8, WINDOW + 2]));
+            // the oldest and the newest
+            assert_ok!(Datalog::erase_item(Origin::signed(sender), 6));
+            assert_ok!(Datalog::erase_item(Origin::signed(sender), WINDOW + 4));
+            assert_eq!(Datalog::data(&sender), data(&[6, 8, WINDOW + 2, WINDOW + 4]));
+            assert_eq!(
+                Balances::reserved_balance(sender),
+                (WINDOW - 5) * (DEPOSIT_PER_ITEM + 9)
+            );
+
+            assert_noop!(
+                Datalog::erase_item(Origin::signed(sender), 8),
+                RuntimeError::RecordNotFound
+            );
+            assert_noop!(
+                Datalog::erase_item(Origin::signed(sender), WINDOW + 5),
+                RuntimeError::RecordNotFound
+            );
+
+            // ring buffer keeps working
+            Timestamp::set_timestamp(1000);
+            assert_ok!(Datalog::record(Origin::signed(sender), vec![1]));
+            assert_eq!(
+                Datalog::data_last(&sender, 1),
+                vec![Item::new(1000, vec![1], WINDOW + 5)]
+            );
+            assert_eq!(Datalog::data(&sender).len() as u64, WINDOW - 4);
+        })
+    }
+
+    #[test]
+    fn test_erase_older_than() {
+        new_test_ext().execute_with(|| {
+            let sender = 1;
+            System::set_block_number(1);
+            store_timed_data(sender, 10);
+
+            assert_ok!(Datalog::erase_older_than(Origin::signed(sender), 45));
+            assert_eq!(Datalog::data(&sender), (5..10).map(timed_item).collect::<Vec<_>>());
+            assert_eq!(Balances::reserved_balance(sender), 5 * (DEPOSIT_PER_ITEM + 9));
+            assert_eq!(
+                last_events(2),
+                vec![
+                    RawEvent::ErasedOlderThan(sender, 45),
+                    RawEvent::DepositReturned(sender, 5 * (DEPOSIT_PER_ITEM + 9)),
+                ]
+            );
+
+            assert_ok!(Datalog::erase_older_than(Origin::signed(sender), 1000));
+            assert_eq!(Datalog::data(&sender), vec![]);
+            assert_eq!(Datalog::datalogidx(&sender).total(), 10);
+        })
+    }
+
+    #[test]
+    fn test_retention() {
+        new_test_ext().execute_with(|| {
+            let sender = 1;
+            System::set_block_number(1);
+            store_timed_data(sender, 10);
+            let reserved = Balances::reserved_balance(sender);
+            assert_ok!(Datalog::set_retention(Origin::signed(sender), Some(50)));
+            assert_eq!(Datalog::retention(sender), Some(50));
+            assert_eq!(
+                last_events(2),
+                vec![
+                    RawEvent::RetentionChanged(sender, Some(50)),
+                    RawEvent::DepositReserved(sender, RETENTION_DEPOSIT),
+                ]
+            );
+            assert_eq!(Balances::reserved_balance(sender), reserved + RETENTION_DEPOSIT);
+            // changed period keeps its deposit
+            assert_ok!(Datalog::set_retention(Origin::signed(sender), Some(50)));
+            assert_eq!(last_events(1), vec![RawEvent::RetentionChanged(sender, Some(50))]);
+            assert_eq!(Balances::reserved_balance(sender), reserved + RETENTION_DEPOSIT);
+
+            // at most `MaxPrunePerRecord` expired records are dropped at once
+            Timestamp::set_timestamp(100);
+            assert_ok!(Datalog::record(Origin::signed(sender), vec![1]));
+            assert_eq!(Datalog::data(&sender)[0], timed_item(2));
+            assert_ok!(Datalog::record(Origin::signed(sender), vec![2]));
+            assert_ok!(Datalog::record(Origin::signed(sender), vec![3]));
+            assert_eq!(Datalog::data(&sender)[0], timed_item(5));
+            assert_ok!(Datalog::record(Origin::signed(sender), vec![4]));
+            assert_eq!(Datalog::data(&sender).len(), 9);
+
+            let reserved = Balances::reserved_balance(sender);
+            assert_ok!(Datalog::set_retention(Origin::signed(sender), None));
+            assert_eq!(
+                last_events(2),
+                vec![
+                    RawEvent::RetentionChanged(sender, None),
+                    RawEvent::DepositReturned(sender, RETENTION_DEPOSIT),
+                ]
+            );
+            assert_eq!(Balances::reserved_balance(sender), reserved - RETENTION_DEPOSIT);
+            Timestamp::set_timestamp(1000);
+            assert_ok!(Datalog::record(Origin::signed(sender), vec![5]));
+            assert_eq!(Datalog::data(&sender).len(), 10);
+        })
+    }
+
+    #[test]
+    fn test_force_erase() {
+        let sender = 1;
+        let mut ext = new_test_ext();
+        ext.execute_with(|| {
+            System::set_block_number(1);
+            store_timed_data(sender, 5);
+
+            assert_noop!(
+                Datalog::force_erase(Origin::signed(2), sender, 5),
+                DispatchError::BadOrigin
+            );
+            let info = Datalog::force_erase(Origin::root(), sender, 4).unwrap();
+            assert_eq!(
+                info.actual_weight,
+                Some(<Runtime as Trait>::WeightInfo::force_erase(5, 4))
+            );
+            assert_eq!(Datalog::data(&sender), vec![]);
+            assert_eq!(Datalog::datalogidx(&sender).total(), 5);
+            assert_eq!(Balances::reserved_balance(sender), 0);
+            assert_eq!(
+                last_events(2),
+                vec![
+                    RawEvent::ForceErased(sender),
+                    RawEvent::DepositReturned(sender, 5 * (DEPOSIT_PER_ITEM + 9)),
+                ]
+            );
+        });
+        ext.persist_offchain_overlay();
+
+        // only hashes of erased records are left
+        let db = ext.offchain_db();
+        assert!(db.get(STORAGE_PREFIX, &archive_key(&sender, 0)).is_some());
+        for seq in 1..5 {
+            assert_eq!(db.get(STORAGE_PREFIX, &archive_key(&sender, seq)), None);
+            let key = mmr_node_key(&sender, mmr::leaf_pos(seq));
+            assert!(db.get(STORAGE_PREFIX, &key).is_some());
+        }
+    }
+
+    #[test]
+    fn test_freeze() {
+        new_test_ext().execute_with(|| {
+            let (sender, writer) = (1, 2);
+            let record = b"datalog".to_vec();
+            System::set_block_number(1);
+            assert_ok!(Datalog::add_writer(Origin::signed(sender), writer, None, 0));
+
+            assert_noop!(
+                Datalog::freeze(Origin::signed(2), sender),
+                DispatchError::BadOrigin
+            );
+            assert_noop!(
+                Datalog::thaw(Origin::root(), sender),
+                RuntimeError::NotFrozen
+            );
+            assert_ok!(Datalog::freeze(Origin::root(), sender));
+            assert!(Datalog::frozen(sender));
+            assert_eq!(last_events(1), vec![RawEvent::Frozen(sender)]);
+            assert_noop!(
+                Datalog::record(Origin::signed(sender), record.clone()),
+                RuntimeError::AccountFrozen
+            );
+            assert_noop!(
+                Datalog::record_for(Origin::signed(writer), sender, record.clone()),
+                RuntimeError::AccountFrozen
+            );
+            // frozen account is still able to erase its datalog
+            assert_ok!(Datalog::erase(Origin::signed(sender)));
+
+            assert_ok!(Datalog::thaw(Origin::root(), sender));
+            assert_eq!(last_events(1), vec![RawEvent::Thawed(sender)]);
+            assert_ok!(Datalog::record(Origin::signed(sender), record));
+        })
+    }
+
+    fn attest(account: u64, record: &Vec<u8>, timestamp: Moment, device: u64) -> TestSignature {
+        TestSignature(device, attestation_payload(&account, record, &timestamp))
+    }
+
+    /// Proof of device key for registration to owner.
+    fn prove(owner: u64, device: u64) -> TestSignature {
+        TestSignature(device, registration_payload(&owner, &System::block_hash(0)))
+    }
+
+    #[test]
+    fn test_attested_records() {
+        new_test_ext().execute_with(|| {
+            let (sender, device) = (1, 10);
+            let record = b"reading".to_vec();
+            let signature = attest(sender, &record, 5, device);
+            System::set_block_number(1);
+            assert_noop!(
+                Datalog::record_attested(
+                    Origin::signed(sender),
+                    record.clone(),
+                    5,
+                    device,
+                    signature.clone()
+                ),
+                RuntimeError::DeviceNotRegistered
+            );
+
+            assert_ok!(Datalog::register_device(
+                Origin::signed(sender),
+                device,
+                prove(sender, device)
+            ));
+            assert_eq!(
+                last_events(2),
+                vec![
+                    RawEvent::DeviceRegistered(sender, device),
+                    RawEvent::DepositReserved(sender, DEVICE_DEPOSIT),
+                ]
+            );
+            assert_noop!(
+                Datalog::register_device(Origin::signed(2), device, prove(2, device)),
+                RuntimeError::DeviceAlreadyRegistered
+            );
+            assert_noop!(
+                Datalog::record_attested(
+                    Origin::signed(sender),
+                    record.clone(),
+                    6,
+                    device,
+                    signature.clone()
+                ),
+                RuntimeError::BadSignature
+            );
+            assert_ok!(Datalog::record_attested(
+                Origin::signed(sender),
+                record.clone(),
+                5,
+                device,
+                signature.clone()
+            ));
+            assert_eq!(
+                last_events(3),
+                vec![
+                    RawEvent::NewRecord(sender, 0, record.clone(), 0, 1),
+                    RawEvent::DepositReserved(sender, Datalog::deposit_of(&record)),
+                    RawEvent::RecordAttested(sender, 0, device),
+                ]
+            );
+            // replay of the same reading
+            assert_noop!(
+                Datalog::record_attested(
+                    Origin::signed(sender),
+                    record.clone(),
+                    5,
+                    device,
+                    signature
+                ),
+                RuntimeError::StaleAttestation
+            );
+            assert_ok!(Datalog::record(Origin::signed(sender), record.clone()));
+
+            assert_eq!(
+                Datalog::entries(&sender),
+                vec![
+                    Entry {
+                        seq: 0,
+                        moment: 0,
+                        record: record.clone(),
+                        attested_by: Some(device)
+                    },
+                    Entry {
+                        seq: 1,
+                        moment: 0,
+                        record,
+                        attested_by: None
+                    },
+                ]
+            );
+
+            // attestation leaves together with the record
+            assert_ok!(Datalog::erase_item(Origin::signed(sender), 0));
+            assert_eq!(Datalog::attestation((sender, 0u64)), None);
+        })
+    }
+
+    #[test]
+    fn test_unregister_device() {
+        new_test_ext().execute_with(|| {
+            let (sender, device) = (1, 10);
+            let record = b"reading".to_vec();
+            System::set_block_number(1);
+            assert_ok!(Datalog::register_device(
+                Origin::signed(sender),
+                device,
+                prove(sender, device)
+            ));
+            assert_eq!(Balances::reserved_balance(sender), DEVICE_DEPOSIT);
+            assert_noop!(
+                Datalog::unregister_device(Origin::signed(2), device),
+                RuntimeError::DeviceNotRegistered
+            );
+            assert_ok!(Datalog::unregister_device(Origin::signed(sender), device));
+            assert_eq!(Datalog::devices(device), None);
+            assert_eq!(
+                last_events(2),
+                vec![
+                    RawEvent::DeviceUnregistered(sender, device),
+                    RawEvent::DepositReturned(sender, DEVICE_DEPOSIT),
+                ]
+            );
+            assert_eq!(Balances::reserved_balance(sender), 0);
+            assert_noop!(
+                Datalog::record_attested(
+                    Origin::signed(sender),
+                    record.clone(),
+                    5,
+                    device,
+                    attest(sender, &record, 5, device)
+                ),
+                RuntimeError::DeviceNotRegistered
+            );
+        })
+    }
+
+    #[test]
+    fn test_device_proof() {
+        new_test_ext().execute_with(|| {
+            let (sender, device) = (1, 10);
+            // device key is not proven
+            let unsigned = TestSignature(device, vec![]);
+            assert_noop!(
+                Datalog::register_device(Origin::signed(sender), device, unsigned),
+                RuntimeError::BadSignature
+            );
+            // proof is made by another key
+            assert_noop!(
+                Datalog::register_device(Origin::signed(sender), device, prove(sender, 11)),
+                RuntimeError::BadSignature
+            );
+            // proof is made for another owner
+            assert_noop!(
+                Datalog::register_device(Origin::signed(sender), device, prove(2, device)),
+                RuntimeError::BadSignature
+            );
+            // owner can't reserve device deposit
+            assert_noop!(
+                Datalog::register_device(Origin::signed(4), device, prove(4, device)),
+                pallet_balances::Error::<Runtime>::InsufficientBalance
+            );
+            assert_eq!(Datalog::devices(device), None);
+        })
+    }
+
+    #[test]
+    fn test_record_batch() {
+        new_test_ext().execute_with(|| {
+            let sender = 1;
+            let records: Vec<_> = (0..5u64).map(|i| i.to_be_bytes().to_vec()).collect();
+            System::set_block_number(1);
+            assert_ok!(Datalog::record(Origin::signed(sender), b"first".to_vec()));
+            assert_ok!(Datalog::record_batch(Origin::signed(sender), records.clone()));
+
+            let mut data = vec![Item::new(0, b"first".to_vec(), 0)];
+            data.extend((0..5).map(|i| Item::new(0, records[i as usize].clone(), i + 1)));
+            assert_eq!(Datalog::data(&sender), data);
+
+            // single event for the whole batch
+            let deposit = records.iter().map(|r| Datalog::deposit_of(r)).sum::<u64>();
+            assert_eq!(
+                last_events(2),
+                vec![
+                    RawEvent::BatchRecorded(sender, 1, 5, 1),
+                    RawEvent::DepositReserved(sender, deposit),
+                ]
+            );
+            assert_eq!(
+                Balances::reserved_balance(sender),
+                deposit + Datalog::deposit_of(&b"first".to_vec())
+            );
+        })
+    }
+
+    #[test]
+    fn test_record_batch_checks() {
+        new_test_ext().execute_with(|| {
+            let sender = 1;
+            let record = b"datalog".to_vec();
+            assert_noop!(
+                Datalog::record_batch(Origin::signed(sender), vec![record.clone(); 6]),
+                RuntimeError::BatchTooLong
+            );
+            assert_noop!(
+                Datalog::record_batch(Origin::signed(sender), vec![record.clone(), vec![0; 513]]),
+                RuntimeError::RecordTooBig
+            );
+            // nothing is written when deposit of the whole batch can't be reserved
+            assert_noop!(
+                Datalog::record_batch(Origin::signed(4), vec![record.clone(); 2]),
+                pallet_balances::Error::<Runtime>::InsufficientBalance
+            );
+
+            assert_ok!(Datalog::record_batch(Origin::signed(sender), vec![]));
+            assert_eq!(Datalog::data(&sender), vec![]);
+
+            // batch longer than window free space drops the oldest records
+            for _ in 0..(WINDOW - 3) {
+                assert_ok!(Datalog::record(Origin::signed(sender), record.clone()));
+            }
+            assert_ok!(Datalog::record_batch(Origin::signed(sender), vec![vec![1]; 5]));
+            let data = Datalog::data(&sender);
+            assert_eq!(data.len() as u64, WINDOW - 1);
+            assert_eq!(data[0], Item::new(0, record, 3));
+            assert_eq!(data[WINDOW as usize - 2], Item::new(0, vec![1], WINDOW + 1));
+        })
+    }
+
+    #[test]
+    fn test_send_record() {
+        new_test_ext().execute_with(|| {
+            let (lab, owner) = (1, 2);
+            let record = b"verified".to_vec();
+            System::set_block_number(1);
+            assert_noop!(
+                Datalog::send(Origin::signed(lab), owner, record.clone()),
+                RuntimeError::SenderNotAllowed
+            );
+
+            assert_ok!(Datalog::allow_sender(Origin::signed(owner), lab));
+            assert_eq!(Balances::reserved_balance(owner), SENDER_DEPOSIT);
+            // allowed sender is not charged twice
+            assert_ok!(Datalog::allow_sender(Origin::signed(owner), lab));
+            assert_eq!(Balances::reserved_balance(owner), SENDER_DEPOSIT);
+            Timestamp::set_timestamp(100);
+            assert_ok!(Datalog::send(Origin::signed(lab), owner, record.clone()));
+            assert_eq!(
+                last_events(4),
+                vec![
+                    RawEvent::SenderAllowed(owner, lab),
+                    RawEvent::DepositReserved(owner, SENDER_DEPOSIT),
+                    RawEvent::RecordSent(lab, owner, 100, record.clone()),
+                    RawEvent::DepositReserved(lab, Datalog::deposit_of(&record)),
+                ]
+            );
+            assert_eq!(
+                Datalog::inbox(&owner)
+                    .into_iter()
+                    .map(|item| item.into())
+                    .collect::<Vec<_>>(),
+                vec![(lab, 100, record.clone())]
+            );
+            // own datalogs are untouched
+            assert_eq!(Datalog::data(&owner), vec![]);
+            assert_eq!(Datalog::data(&lab), vec![]);
+            assert_eq!(Balances::reserved_balance(lab), Datalog::deposit_of(&record));
+
+            assert_ok!(Datalog::disallow_sender(Origin::signed(owner), lab));
+            assert_eq!(
+                last_events(2),
+                vec![
+                    RawEvent::SenderDisallowed(owner, lab),
+                    RawEvent::DepositReturned(owner, SENDER_DEPOSIT),
+                ]
+            );
+            assert_eq!(Balances::reserved_balance(owner), 0);
+            assert_noop!(
+                Datalog::send(Origin::signed(lab), owner, record),
+                RuntimeError::SenderNotAllowed
+            );
+        })
+    }
+
+    #[test]
+    fn test_inbox_recycle_and_clear() {
+        new_test_ext().execute_with(|| {
+            let (lab, auditor, owner) = (1, 2, 3);
+            System::set_block_number(1);
+            assert_ok!(Datalog::allow_sender(Origin::signed(owner), lab));
+            assert_ok!(Datalog::allow_sender(Origin::signed(owner), auditor));
+
+            assert_ok!(Datalog::send(Origin::signed(auditor), owner, vec![0]));
+            for i in 0..(WINDOW - 1) {
+                assert_ok!(Datalog::send(Origin::signed(lab), owner, i.to_be_bytes().to_vec()));
+            }
+            // the oldest record is dropped and its deposit is returned to its sender
+            assert_eq!(Datalog::inbox(&owner).len() as u64, WINDOW - 1);
+            assert_eq!(Balances::reserved_balance(auditor), 0);
+            assert_eq!(
+                Balances::reserved_balance(lab),
+                (WINDOW - 1) * (DEPOSIT_PER_ITEM + 9)
+            );
+            assert_eq!(
+                last_events(3),
+                vec![
+                    RawEvent::DepositReturned(auditor, DEPOSIT_PER_ITEM + 2),
+                    RawEvent::RecordSent(lab, owner, 0, (WINDOW - 2).to_be_bytes().to_vec()),
+                    RawEvent::DepositReserved(lab, DEPOSIT_PER_ITEM + 9),
+                ]
+            );
+
+            let info = Datalog::clear_inbox(Origin::signed(owner)).unwrap();
+            assert_eq!(
+                info.actual_weight,
+                Some(<() as WeightInfo>::clear_inbox(WINDOW as u32 - 1))
+            );
+            assert_eq!(Datalog::inbox(&owner), vec![]);
+            assert_eq!(Balances::reserved_balance(lab), 0);
+            assert_eq!(
+                last_events(2),
+                vec![
+                    RawEvent::DepositReturned(lab, DEPOSIT_PER_ITEM + 9),
+                    RawEvent::InboxCleared(owner),
+                ]
+            );
+        })
+    }
+
+    #[test]
+    fn test_record_change() {
+        new_test_ext().execute_with(|| {
+            let sender = 1;
+            System::set_block_number(1);
+            Timestamp::set_timestamp(1);
+            assert_ok!(Datalog::record(Origin::signed(sender), b"datalog".to_vec()));
+            assert_ok!(Datalog::erase(Origin::signed(sender)));
+
+            let changes: Vec<_> = last_events(10)
+                .into_iter()
+                .filter_map(|event| event.record_change())
+                .collect();
+            assert_eq!(
+                changes,
+                vec![
+                    RecordChange::NewRecord(sender, 1, b"datalog".to_vec(), 0),
+                    RecordChange::Erased(sender),
+                ]
+            );
+            assert_eq!(changes[0].account(), &sender);
+        })
+    }
+
+    #[test]
+    fn test_archive_key() {
+        assert_ne!(archive_key(&1u64, 0), archive_key(&1u64, 1));
+        assert_ne!(archive_key(&1u64, 0), archive_key(&2u64, 0));
+        assert!(archive_key(&1u64, 0).starts_with(&ARCHIVE_PREFIX.encode()));
+    }
+
+    #[test]
+    fn test_archive() {
+        let sender = 1;
+        let mut ext = new_test_ext();
+        ext.execute_with(|| {
+            // overwritten records are kept in archive
+            for i in 0..(WINDOW + 5) {
+                Timestamp::set_timestamp(i);
+                assert_ok!(Datalog::record(Origin::signed(sender), i.to_be_bytes().to_vec()));
+            }
+        });
+        ext.persist_offchain_overlay();
+
+        let db = ext.offchain_db();
+        for seq in 0..(WINDOW + 5) {
+            let value = db.get(STORAGE_PREFIX, &archive_key(&sender, seq)).unwrap();
+            let record = <(Moment, Vec<u8>)>::decode(&mut &value[..]).unwrap();
+            assert_eq!(record, (seq, seq.to_be_bytes().to_vec()));
+
+            let value = db.get(STORAGE_PREFIX, &mmr_node_key(&sender, mmr::leaf_pos(seq)));
+            assert_eq!(
+                H256::decode(&mut &value.unwrap()[..]).unwrap(),
+                mmr::leaf_hash::<BlakeTwo256, _, _>(&record.0, &record.1),
+            );
+        }
+        assert_eq!(db.get(STORAGE_PREFIX, &archive_key(&sender, WINDOW + 5)), None);
+        assert_eq!(db.get(STORAGE_PREFIX, &archive_key(&2u64, 0)), None);
+    }
+
+    #[test]
+    fn test_proof_from_nodes() {
+        let sender = 1;
+        let leaf_count = 2 * WINDOW + 3;
+        let mut ext = new_test_ext();
+        let (root, peaks) = ext.execute_with(|| {
+            for i in 0..leaf_count {
+                Timestamp::set_timestamp(i);
+                assert_ok!(Datalog::record(Origin::signed(sender), i.to_be_bytes().to_vec()));
+            }
+            (Datalog::mmr_root(sender), Datalog::mmr_peaks(sender))
+        });
+        ext.persist_offchain_overlay();
+
+        // every node of the range is indexed
+        let db = ext.offchain_db();
+        let node = |pos| {
+            db.get(STORAGE_PREFIX, &mmr_node_key(&sender, pos))
+                .map(|value| H256::decode(&mut &value[..]).unwrap())
+        };
+        assert!((0..mmr::size(leaf_count)).all(|pos| node(pos).is_some()));
+        assert_eq!(node(mmr::size(leaf_count)), None);
+
+        for i in 0..leaf_count {
+            let leaf = mmr::leaf_hash::<BlakeTwo256, _, _>(&i, &i.to_be_bytes().to_vec());
+            let proof =
+                mmr::proof_from_nodes::<BlakeTwo256>(i, leaf_count, peaks.clone(), node).unwrap();
+            assert!(mmr::verify_proof::<BlakeTwo256>(&root, &leaf, &proof));
+        }
+        assert_eq!(
+            mmr::proof_from_nodes::<BlakeTwo256>(leaf_count, leaf_count, peaks, node),
+            None
+        );
     }
 
     #[test]
@@ -404,10 +2582,465 @@ mod tests {
         })
     }
 
+    #[test]
+    fn test_ringbuffer_len() {
+        let mut idx: RingBufferIndex = Default::default();
+        assert!(idx.is_empty());
+        assert_eq!(idx.len(WINDOW), 0);
+
+        for _ in 0..(WINDOW + 5) {
+            idx.add(WINDOW);
+        }
+        assert!(!idx.is_empty());
+        assert_eq!(idx.len(WINDOW), WINDOW - 1);
+        assert_eq!(idx.nth(0, WINDOW), idx.start);
+        assert_eq!(idx.nth(WINDOW - 2, WINDOW), 4);
+    }
+
+    fn store_timed_data(sender: u64, count: u64) {
+        for i in 0..count {
+            Timestamp::set_timestamp(i * 10);
+            assert_ok!(Datalog::record(
+                Origin::signed(sender),
+                i.to_be_bytes().to_vec()
+            ));
+        }
+    }
+
+    fn timed_item(i: u64) -> Item {
+        Item::new(i * 10, i.to_be_bytes().to_vec(), i)
+    }
+
+    #[test]
+    fn test_data_range() {
+        new_test_ext().execute_with(|| {
+            let sender = 1;
+            store_timed_data(sender, WINDOW + 10);
+
+            // records 0..11 are overwritten
+            assert_eq!(
+                Datalog::data_range(&sender, 0, 125),
+                (11..13).map(timed_item).collect::<Vec<_>>()
+            );
+            assert_eq!(
+                Datalog::data_range(&sender, 155, 180),
+                (16..19).map(timed_item).collect::<Vec<_>>()
+            );
+            assert_eq!(
+                Datalog::data_range(&sender, 250, 1000),
+                (25..(WINDOW + 10)).map(timed_item).collect::<Vec<_>>()
+            );
+            assert_eq!(Datalog::data_range(&sender, 1000, 2000), vec![]);
+            assert_eq!(Datalog::data_range(&sender, 180, 170), vec![]);
+            assert_eq!(Datalog::data_range(&2, 0, 1000), vec![]);
+        })
+    }
+
+    #[test]
+    fn test_data_last() {
+        new_test_ext().execute_with(|| {
+            let sender = 1;
+            store_timed_data(sender, WINDOW + 10);
+
+            assert_eq!(
+                Datalog::data_last(&sender, 3),
+                (27..30).map(timed_item).collect::<Vec<_>>()
+            );
+            assert_eq!(Datalog::data_last(&sender, 100), Datalog::data(&sender));
+            assert_eq!(Datalog::data_last(&sender, 0), vec![]);
+        })
+    }
+
+    #[test]
+    fn test_data_page() {
+        new_test_ext().execute_with(|| {
+            let sender = 1;
+            store_timed_data(sender, WINDOW + 10);
+
+            let (page, cursor) = Datalog::data_page(&sender, 0, 8);
+            assert_eq!(page, (11..19).map(timed_item).collect::<Vec<_>>());
+            assert_eq!(cursor, Some(8));
+
+            let (page, cursor) = Datalog::data_page(&sender, 8, 8);
+            assert_eq!(page, (19..27).map(timed_item).collect::<Vec<_>>());
+            assert_eq!(cursor, Some(16));
+
+            let (page, cursor) = Datalog::data_page(&sender, 16, 8);
+            assert_eq!(page, (27..30).map(timed_item).collect::<Vec<_>>());
+            assert_eq!(cursor, None);
+
+            assert_eq!(Datalog::data_page(&sender, 100, 8), (vec![], None));
+        })
+    }
+
+    #[test]
+    fn test_record_deposit() {
+        new_test_ext().execute_with(|| {
+            let sender = 1;
+            let record = b"datalog".to_vec();
+            System::set_block_number(1);
+            // item deposit plus one byte per encoded record byte
+            let deposit = DEPOSIT_PER_ITEM + 8;
+            assert_eq!(Datalog::deposit_of(&record), deposit);
+
+            assert_ok!(Datalog::record(Origin::signed(sender), record.clone()));
+            assert_eq!(Balances::reserved_balance(sender), deposit);
+            assert_eq!(Datalog::datalogdeposit((sender, 0u64)), deposit);
+
+            assert_ok!(Datalog::record(Origin::signed(sender), record));
+            assert_eq!(Balances::reserved_balance(sender), 2 * deposit);
+
+            assert_ok!(Datalog::erase(Origin::signed(sender)));
+            assert_eq!(Balances::reserved_balance(sender), 0);
+            assert_eq!(Datalog::datalogdeposit((sender, 0u64)), 0);
+            assert_eq!(
+                last_events(2),
+                vec![
+                    RawEvent::Erased(sender),
+                    RawEvent::DepositReturned(sender, 2 * deposit),
+                ]
+            );
+        })
+    }
+
+    #[test]
+    fn test_recycle_deposit() {
+        new_test_ext().execute_with(|| {
+            let sender = 1;
+            System::set_block_number(1);
+            for i in 0..(WINDOW + 10) {
+                assert_ok!(Datalog::record(
+                    Origin::signed(sender),
+                    i.to_be_bytes().to_vec()
+                ));
+            }
+
+            // deposits of overwritten records are returned
+            let deposit = DEPOSIT_PER_ITEM + 9;
+            assert_eq!(
+                Balances::reserved_balance(sender),
+                (WINDOW - 1) * deposit
+            );
+            assert_eq!(Datalog::datalogdeposit((sender, 10u64)), 0);
+            assert!(!DatalogItem::<Runtime>::contains_key((sender, 10u64)));
+            let last = WINDOW + 9;
+            assert_eq!(
+                last_events(3),
+                vec![
+                    RawEvent::NewRecord(sender, 0, last.to_be_bytes().to_vec(), last, 1),
+                    RawEvent::DepositReserved(sender, deposit),
+                    RawEvent::DepositReturned(sender, deposit),
+                ]
+            );
+        })
+    }
+
+    #[test]
+    fn test_insufficient_deposit() {
+        new_test_ext().execute_with(|| {
+            assert_noop!(
+                Datalog::record(Origin::signed(4), b"datalog".to_vec()),
+                pallet_balances::Error::<Runtime>::InsufficientBalance
+            );
+            assert_eq!(Datalog::data(&4), vec![]);
+        })
+    }
+
+    #[test]
+    fn test_window_size_bounds() {
+        new_test_ext().execute_with(|| {
+            let sender = 1;
+            assert_eq!(Datalog::window_size(&sender), WINDOW);
+            assert_noop!(
+                Datalog::set_window_size(Origin::signed(sender), 1),
+                RuntimeError::BadWindowSize
+            );
+            assert_noop!(
+                Datalog::set_window_size(Origin::signed(sender), 101),
+                RuntimeError::BadWindowSize
+            );
+            assert_ok!(Datalog::set_window_size(Origin::signed(sender), 100));
+            assert_eq!(Datalog::window_size(&sender), 100);
+            assert_eq!(Datalog::window_size(&2), WINDOW);
+        })
+    }
+
+    #[test]
+    fn test_shrink_window() {
+        new_test_ext().execute_with(|| {
+            let sender = 1;
+            System::set_block_number(1);
+            store_timed_data(sender, WINDOW + 10);
+
+            assert_ok!(Datalog::set_window_size(Origin::signed(sender), 10));
+            assert_eq!(
+                last_events(2),
+                vec![
+                    RawEvent::WindowSizeChanged(sender, 10),
+                    RawEvent::DepositReturned(sender, 10 * (DEPOSIT_PER_ITEM + 9)),
+                ]
+            );
+            assert_eq!(
+                Datalog::data(&sender),
+                (21..30).map(timed_item).collect::<Vec<_>>()
+            );
+            assert_eq!(
+                Datalog::datalogidx(&sender),
+                RingBufferIndex { start: 0, end: 9, total: WINDOW + 10 }
+            );
+            // pruned items and their deposits are gone
+            for slot in 9..WINDOW {
+                assert!(!DatalogItem::<Runtime>::contains_key((sender, slot)));
+                assert!(!DatalogDeposit::<Runtime>::contains_key((sender, slot)));
+            }
+            assert_eq!(
+                Balances::reserved_balance(sender),
+                9 * (DEPOSIT_PER_ITEM + 9)
+            );
+
+            // ring buffer keeps working with the new window
+            assert_ok!(Datalog::record(Origin::signed(sender), vec![1]));
+            assert_eq!(
+                Datalog::data_last(&sender, 1),
+                vec![Item::new(290, vec![1], WINDOW + 10)]
+            );
+            assert_eq!(Datalog::data(&sender).len(), 9);
+            assert_eq!(
+                Datalog::datalogidx(&sender),
+                RingBufferIndex { start: 1, end: 0, total: WINDOW + 11 }
+            );
+        })
+    }
+
+    #[test]
+    fn test_grow_window() {
+        new_test_ext().execute_with(|| {
+            let sender = 1;
+            store_timed_data(sender, WINDOW + 10);
+
+            let info = Datalog::set_window_size(Origin::signed(sender), 40).unwrap();
+            assert_eq!(
+                Datalog::data(&sender),
+                (11..30).map(timed_item).collect::<Vec<_>>()
+            );
+            // weight is charged for moved records only
+            assert_eq!(
+                info.actual_weight,
+                Some(<() as WeightInfo>::set_window_size(WINDOW as u32 - 1))
+            );
+
+            for _ in 0..30 {
+                assert_ok!(Datalog::record(Origin::signed(sender), vec![1]));
+            }
+            assert_eq!(Datalog::data(&sender).len(), 39);
+            assert_eq!(Datalog::data(&sender)[0], timed_item(21));
+        })
+    }
+
+    fn legacy_data(count: u64) -> Vec<(Moment, Vec<u8>)> {
+        (0..count).map(|i| (i, i.to_be_bytes().to_vec())).collect()
+    }
+
+    /// Put ring buffer of baseline release into storage.
+    fn baseline_ring_buffer(account: u64, records: &[(Moment, Vec<u8>)]) {
+        for (slot, (moment, record)) in records.iter().enumerate() {
+            let key = DatalogItem::<Runtime>::hashed_key_for((account, slot as u64));
+            unhashed::put(&key, &(codec::Compact(*moment), record));
+        }
+        let key = DatalogIndex::<Runtime>::hashed_key_for(account);
+        let end = records.len() as u64;
+        unhashed::put(&key, &(codec::Compact(0u64), codec::Compact(end)));
+    }
+
+    /// Initialize blocks until storage is migrated, returns number of blocks.
+    fn migrate() -> u64 {
+        let mut blocks = 0;
+        while StorageVersion::get() != Releases::V2 {
+            blocks += 1;
+            System::set_block_number(blocks);
+            <Datalog as OnInitialize<u64>>::on_initialize(blocks);
+        }
+        blocks
+    }
+
+    #[test]
+    fn test_migrate_legacy_data() {
+        new_test_ext().execute_with(|| {
+            StorageVersion::put(Releases::V1);
+            super::Datalog::<Runtime>::insert(1, legacy_data(5));
+            super::Datalog::<Runtime>::insert(2, legacy_data(WINDOW + 5));
+
+            migrate();
+
+            assert_eq!(Datalog::datalog(1), vec![]);
+            assert_eq!(Datalog::datalog(2), vec![]);
+            assert_eq!(
+                Datalog::data(&1),
+                (0..5)
+                    .map(|i| Item::legacy(i, i.to_be_bytes().to_vec(), i))
+                    .collect::<Vec<_>>()
+            );
+            assert_eq!(
+                Datalog::datalogidx(1),
+                RingBufferIndex { start: 0, end: 5, total: 5 }
+            );
+            // only the newest records fitting the window are kept
+            assert_eq!(
+                Datalog::data(&2),
+                (6..(WINDOW + 5))
+                    .map(|i| Item::legacy(i, i.to_be_bytes().to_vec(), i - 6))
+                    .collect::<Vec<_>>()
+            );
+            // migrated records are committed into history
+            assert_eq!(Datalog::mmr_leaves(2), WINDOW - 1);
+            assert_eq!(Datalog::block_of(&1, 1), None);
+            assert_eq!(MigrationCursor::get(), None);
+        })
+    }
+
+    #[test]
+    fn test_migrate_baseline_ring_buffer() {
+        new_test_ext().execute_with(|| {
+            StorageVersion::put(Releases::V1);
+            let record = b"datalog".to_vec();
+
+            // account 1 has written into the ring buffer but still has legacy data
+            super::Datalog::<Runtime>::insert(1, legacy_data(3));
+            baseline_ring_buffer(1, &[(100, record.clone())]);
+            // account 2 uses the ring buffer only
+            baseline_ring_buffer(2, &[(100, record.clone())]);
+            // account 3 has full ring buffer
+            baseline_ring_buffer(3, &vec![(100, record.clone()); WINDOW as usize - 1]);
+            super::Datalog::<Runtime>::insert(3, legacy_data(3));
+
+            migrate();
+
+            let mut data: Vec<_> = (0..3)
+                .map(|i| Item::legacy(i, i.to_be_bytes().to_vec(), i))
+                .collect();
+            data.push(Item::legacy(100, record.clone(), 3));
+            assert_eq!(Datalog::data(&1), data);
+            assert_eq!(Datalog::datalog(1), vec![]);
+
+            assert_eq!(Datalog::data(&2), vec![Item::legacy(100, record.clone(), 0)]);
+            assert_eq!(
+                Datalog::datalogidx(2),
+                RingBufferIndex { start: 0, end: 1, total: 1 }
+            );
+
+            // full window: legacy records are older and dropped
+            assert_eq!(
+                Datalog::data(&3),
+                (0..(WINDOW - 1))
+                    .map(|i| Item::legacy(100, record.clone(), i))
+                    .collect::<Vec<_>>()
+            );
+            assert_eq!(Datalog::datalog(3), vec![]);
+
+            // ring buffer keeps working
+            System::set_block_number(7);
+            assert_ok!(Datalog::record(Origin::signed(2), record.clone()));
+            assert_eq!(Datalog::block_of(&2, 1), Some(7));
+        })
+    }
+
+    #[test]
+    fn test_migrate_phased() {
+        new_test_ext().execute_with(|| {
+            StorageVersion::put(Releases::V1);
+            let record = b"datalog".to_vec();
+            for account in 1..6 {
+                super::Datalog::<Runtime>::insert(account, legacy_data(1));
+            }
+            baseline_ring_buffer(6, &[(100, record.clone())]);
+            baseline_ring_buffer(7, &[(100, record.clone())]);
+
+            <Datalog as OnInitialize<u64>>::on_initialize(1);
+            assert_eq!(StorageVersion::get(), Releases::V1);
+            assert!(MigrationCursor::get().is_some());
+            assert_noop!(
+                Datalog::record(Origin::signed(1), record.clone()),
+                RuntimeError::Migrating
+            );
+
+            // ring buffers are migrated in the first block, then switch to legacy
+            // vectors, five legacy vectors and completion take two steps per block
+            assert_eq!(migrate(), 4);
+            assert_eq!(MigrationCursor::get(), None);
+            for account in 1..6 {
+                assert_eq!(Datalog::data(&account), vec![Item::legacy(0, vec![0; 8], 0)]);
+            }
+            assert_eq!(Datalog::data(&7), vec![Item::legacy(100, record.clone(), 0)]);
+            assert_ok!(Datalog::record(Origin::signed(1), record));
+        })
+    }
+
+    #[test]
+    fn test_migrate_once() {
+        new_test_ext().execute_with(|| {
+            super::Datalog::<Runtime>::insert(1, legacy_data(3));
+            <Datalog as OnInitialize<u64>>::on_initialize(1);
+
+            assert_eq!(Datalog::datalog(1), legacy_data(3));
+            assert_eq!(Datalog::data(&1), vec![]);
+        })
+    }
+
+    #[test]
+    fn test_record_block() {
+        new_test_ext().execute_with(|| {
+            let sender = 1;
+            let record = b"datalog".to_vec();
+            for block in 1..4 {
+                System::set_block_number(block);
+                assert_ok!(Datalog::record(Origin::signed(sender), record.clone()));
+            }
+
+            assert_eq!(Datalog::data(&sender)[2], Item::new(0, record.clone(), 2));
+            assert_eq!(Datalog::block_of(&sender, 0), Some(1));
+            assert_eq!(Datalog::block_of(&sender, 2), Some(3));
+
+            assert_ok!(Datalog::erase_item(Origin::signed(sender), 1));
+            assert_eq!(Datalog::block_of(&sender, 1), None);
+            assert_eq!(Datalog::block_of(&sender, 2), Some(3));
+        })
+    }
+
     fn hash2vec(ss58hash: &str) -> Vec<u8> {
         ss58hash.from_base58().unwrap()
     }
 
+    #[test]
+    fn test_typed_records() {
+        let cid = hash2vec("QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4");
+        assert_eq!(TypedRecord::from(cid.clone()).format(), Format::Raw);
+        assert!(TypedRecord::new(Format::Cid, cid.clone()).is_valid());
+        assert!(TypedRecord::new(Format::Raw, cid.clone()).is_valid());
+        assert!(!TypedRecord::new(Format::Text, cid).is_valid());
+
+        assert!(TypedRecord::new(Format::Text, "datalog ✓".into()).is_valid());
+        assert!(TypedRecord::new(Format::Scale, 42u64.encode()).is_valid());
+        assert_eq!(b"datalog".to_vec().format(), Format::Raw);
+    }
+
+    #[test]
+    fn test_cid_validation() {
+        // CIDv0
+        let cid = hash2vec("QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4");
+        assert!(record::is_valid_cid(&cid));
+        assert!(!record::is_valid_cid(&cid[..33]));
+        assert!(!record::is_valid_cid(&[cid.clone(), vec![0]].concat()));
+
+        // CIDv1 in base58btc multibase
+        let cid = hash2vec(&"zdj7WWYAEceQ6ncfPZeRFjozov4dC7FaxU7SuMwzW4VuYBDta"[1..]);
+        assert!(record::is_valid_cid(&cid));
+        assert!(!record::is_valid_cid(&cid[..cid.len() - 1]));
+
+        assert!(!record::is_valid_cid(&[]));
+        assert!(!record::is_valid_cid(b"datalog"));
+        assert!(!record::is_valid_cid(&[0x01, 0xff, 0xff]));
+    }
+
     #[test]
     fn test_store_ipfs_hashes() {
         new_test_ext().execute_with(|| {
@@ -415,7 +3048,7 @@ mod tests {
             let record = hash2vec("QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4");
 
             assert_ok!(Datalog::record(Origin::signed(sender), record.clone()));
-            assert_eq!(Datalog::data(&sender), vec![Item::new(0, record.clone())]);
+            assert_eq!(Datalog::data(&sender), vec![Item::new(0, record.clone(), 0)]);
 
             let record2 = hash2vec("zdj7WWYAEceQ6ncfPZeRFjozov4dC7FaxU7SuMwzW4VuYBDta");
 
@@ -424,8 +3057,8 @@ mod tests {
             assert_eq!(
                 Datalog::data(&sender),
                 vec![
-                    Item::new(0, record.clone()),
-                    Item::new(100, record2.clone()),
+                    Item::new(0, record.clone(), 0),
+                    Item::new(100, record2.clone(), 1),
                 ]
             );
             let record3 = hash2vec("QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz2");
@@ -435,11 +3068,155 @@ mod tests {
             assert_eq!(
                 Datalog::data(&sender),
                 vec![
-                    Item::new(0, record),
-                    Item::new(100, record2),
-                    Item::new(200, record3),
+                    Item::new(0, record, 0),
+                    Item::new(100, record2, 1),
+                    Item::new(200, record3, 2),
                 ]
             );
         })
     }
+
+    /// Runtime storing typed records, as the node runtime does.
+    mod typed {
+        use super::*;
+
+        impl_outer_origin! {
+            pub enum Origin for TypedRuntime {}
+        }
+
+        #[derive(Clone, PartialEq, Eq, Debug)]
+        pub struct TypedRuntime;
+        type Datalog = Module<TypedRuntime>;
+        type Timestamp = pallet_timestamp::Module<TypedRuntime>;
+
+        impl frame_system::Trait for TypedRuntime {
+            type Origin = Origin;
+            type Index = u64;
+            type BlockNumber = u64;
+            type Call = ();
+            type Hash = H256;
+            type Hashing = BlakeTwo256;
+            type AccountId = u64;
+            type Lookup = IdentityLookup<Self::AccountId>;
+            type Header = Header;
+            type Event = ();
+            type BlockHashCount = BlockHashCount;
+            type Version = ();
+            type PalletInfo = ();
+            type AccountData = pallet_balances::AccountData<u64>;
+            type OnNewAccount = ();
+            type OnKilledAccount = ();
+            type DbWeight = ();
+            type BaseCallFilter = ();
+            type SystemWeightInfo = ();
+            type BlockExecutionWeight = BlockExecutionWeight;
+            type ExtrinsicBaseWeight = ExtrinsicBaseWeight;
+            type MaximumExtrinsicWeight = MaximumExtrinsicWeight;
+            type MaximumBlockWeight = MaximumBlockWeight;
+            type MaximumBlockLength = MaximumBlockLength;
+            type AvailableBlockRatio = AvailableBlockRatio;
+        }
+
+        impl pallet_timestamp::Trait for TypedRuntime {
+            type Moment = Moment;
+            type OnTimestampSet = ();
+            type MinimumPeriod = ();
+            type WeightInfo = ();
+        }
+
+        impl pallet_balances::Trait for TypedRuntime {
+            type MaxLocks = ();
+            type Balance = u64;
+            type DustRemoval = ();
+            type Event = ();
+            type ExistentialDeposit = ExistentialDeposit;
+            type AccountStore = frame_system::Module<TypedRuntime>;
+            type WeightInfo = ();
+        }
+
+        impl Trait for TypedRuntime {
+            type Time = Timestamp;
+            type Record = TypedRecord;
+            type Event = ();
+            type WindowSize = WindowSize;
+            type MaximumWindowSize = MaximumWindowSize;
+            type MaximumMessageSize = MaximumMessageSize;
+            type Currency = pallet_balances::Module<TypedRuntime>;
+            type DepositPerItem = DepositPerItem;
+            type DepositPerByte = DepositPerByte;
+            type WriterDeposit = WriterDeposit;
+            type SenderDeposit = SenderDeposit;
+            type RetentionDeposit = RetentionDeposit;
+            type DeviceDeposit = DeviceDeposit;
+            type MaxPrunePerRecord = MaxPrunePerRecord;
+            type MaxBatchLength = MaxBatchLength;
+            type BatchRecordEvents = BatchRecordEvents;
+            type MaxMigratedPerBlock = MaxMigratedPerBlock;
+            type ForceOrigin = frame_system::EnsureRoot<u64>;
+            type Signature = TestSignature;
+            type Public = UintAuthorityId;
+            #[cfg(feature = "runtime-benchmarks")]
+            type DeviceKeys = TestDeviceKeys;
+            type WeightInfo = ();
+        }
+
+        fn new_test_ext() -> frame_support::sp_io::TestExternalities {
+            let mut storage = frame_system::GenesisConfig::default()
+                .build_storage::<TypedRuntime>()
+                .unwrap();
+            pallet_balances::GenesisConfig::<TypedRuntime> {
+                balances: vec![(1, 1_000_000)],
+            }
+            .assimilate_storage(&mut storage)
+            .unwrap();
+            GenesisConfig::default()
+                .assimilate_storage::<TypedRuntime>(&mut storage)
+                .unwrap();
+            storage.into()
+        }
+
+        #[test]
+        fn test_record_validation() {
+            new_test_ext().execute_with(|| {
+                let sender = 1;
+                Timestamp::set_timestamp(1);
+
+                // truncated digest of CIDv0
+                assert_noop!(
+                    Datalog::record(
+                        Origin::signed(sender),
+                        TypedRecord::new(Format::Cid, vec![0x12, 0x20, 0x42]),
+                    ),
+                    Error::<TypedRuntime>::InvalidRecord
+                );
+                // CIDv1 with unterminated codec varint
+                assert_noop!(
+                    Datalog::record(
+                        Origin::signed(sender),
+                        TypedRecord::new(Format::Cid, vec![0x01, 0xff, 0xff]),
+                    ),
+                    Error::<TypedRuntime>::InvalidRecord
+                );
+                assert_noop!(
+                    Datalog::record(
+                        Origin::signed(sender),
+                        TypedRecord::new(Format::Text, vec![0xff, 0xfe]),
+                    ),
+                    Error::<TypedRuntime>::InvalidRecord
+                );
+
+                let mut cid = vec![0x12, 0x20];
+                cid.extend_from_slice(&[0x42; 32]);
+                assert_ok!(Datalog::record(
+                    Origin::signed(sender),
+                    TypedRecord::new(Format::Cid, cid),
+                ));
+                assert_ok!(Datalog::record(
+                    Origin::signed(sender),
+                    TypedRecord::new(Format::Text, "datalog".into()),
+                ));
+                assert_eq!(Datalog::data(&sender).len(), 2);
+            })
+        }
+    }
 }
@@ -0,0 +1,129 @@
+///////////////////////////////////////////////////////////////////////////////
+//
+//  Copyright 2018-2020 Airalab <research@aira.life>
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+///////////////////////////////////////////////////////////////////////////////
+//! Datalog storage migrations.
+use super::*;
+use frame_support::{
+    sp_io::storage::next_key,
+    storage::{unhashed, StoragePrefixedMap},
+};
+
+/// Ring buffer item of baseline release, record is raw bytes.
+#[derive(Decode)]
+struct RawRingBufferItem<T: Trait>(#[codec(compact)] MomentOf<T>, Vec<u8>);
+
+/// Ring buffer index of baseline release, without total counter.
+#[derive(Decode, Default)]
+struct UnsequencedRingBufferIndex {
+    #[codec(compact)]
+    start: u64,
+    #[codec(compact)]
+    end: u64,
+}
+
+/// Migrate part of baseline datalog storage into the current layout.
+///
+/// Accounts of `DatalogIndex` are migrated in key order after `MigrationCursor`,
+/// then accounts having legacy `Datalog` vectors only. At most `MaxMigratedPerBlock`
+/// accounts are migrated at once and every account has at most `WindowSize - 1` ring
+/// buffer records plus one legacy vector, so a step never touches more than
+/// `MaxMigratedPerBlock * (WindowSize + 1)` storage items. `StorageVersion` is set
+/// to `V2` when nothing is left.
+pub fn migrate_step<T: Trait>() -> Weight {
+    let index_prefix = DatalogIndex::<T>::final_prefix();
+    let legacy_prefix = Datalog::<T>::final_prefix();
+    let mut cursor = MigrationCursor::get().unwrap_or_else(|| index_prefix.to_vec());
+    let mut weight = T::DbWeight::get().reads(1);
+
+    for _ in 0..T::MaxMigratedPerBlock::get() {
+        weight = weight.saturating_add(T::DbWeight::get().reads(1));
+        if cursor.starts_with(&index_prefix) {
+            // legacy vectors of ring buffer owners are migrated together with ring buffers
+            match next_key(&cursor).filter(|key| key.starts_with(&index_prefix)) {
+                Some(key) => {
+                    // `twox_64_concat` hash is followed by account
+                    let account = T::AccountId::decode(&mut &key[index_prefix.len() + 8..]);
+                    if let Ok(account) = account {
+                        let idx = unhashed::get(&key).unwrap_or_default();
+                        weight = weight.saturating_add(migrate_account::<T>(&account, idx));
+                    }
+                    cursor = key;
+                }
+                None => cursor = legacy_prefix.to_vec(),
+            }
+        } else {
+            match next_key(&legacy_prefix).filter(|key| key.starts_with(&legacy_prefix)) {
+                Some(key) => {
+                    // `blake2_128_concat` hash is followed by account
+                    let account = T::AccountId::decode(&mut &key[legacy_prefix.len() + 16..]);
+                    match account {
+                        Ok(account) => {
+                            let idx = Default::default();
+                            weight = weight.saturating_add(migrate_account::<T>(&account, idx));
+                        }
+                        Err(_) => unhashed::kill(&key),
+                    }
+                }
+                None => {
+                    StorageVersion::put(Releases::V2);
+                    MigrationCursor::kill();
+                    return weight.saturating_add(T::DbWeight::get().writes(2));
+                }
+            }
+        }
+    }
+
+    MigrationCursor::put(cursor);
+    weight.saturating_add(T::DbWeight::get().writes(1))
+}
+
+/// Move baseline ring buffer and legacy vector of account into the current ring buffer.
+///
+/// Legacy records are older than any ring buffer record of the same account, so they
+/// are placed in front of it and only the newest records fitting into the window are
+/// kept. Kept records are numbered from zero and committed into account`s history.
+fn migrate_account<T: Trait>(account: &T::AccountId, idx: UnsequencedRingBufferIndex) -> Weight {
+    let window_size = T::WindowSize::get();
+    let legacy_key = Datalog::<T>::hashed_key_for(account);
+    let mut records: Vec<(MomentOf<T>, Vec<u8>)> =
+        unhashed::take(&legacy_key).unwrap_or_default();
+
+    let idx = RingBufferIndex { start: idx.start, end: idx.end, total: 0 };
+    let len = idx.len(window_size).min(window_size.saturating_sub(1));
+    for n in 0..len {
+        let key = DatalogItem::<T>::hashed_key_for((account, idx.nth(n, window_size)));
+        if let Some(RawRingBufferItem::<T>(moment, record)) = unhashed::take(&key) {
+            records.push((moment, record));
+        }
+    }
+
+    let skip = records.len().saturating_sub(window_size.saturating_sub(1) as usize);
+    let mut idx = RingBufferIndex::default();
+    for (moment, record) in records.into_iter().skip(skip) {
+        let item = RingBufferItem(moment, record.into(), idx.total, None);
+        Module::<T>::commit_history(account, &item);
+        DatalogItem::<T>::insert((account, idx.add(window_size)), item);
+    }
+    let migrated = idx.total;
+    DatalogIndex::<T>::insert(account, idx);
+
+    // index and legacy vector, every removed item, every kept item and its history
+    let reads_writes = 2u64
+        .saturating_add(len)
+        .saturating_add(4 * migrated);
+    T::DbWeight::get().reads_writes(reads_writes, reads_writes)
+}
@@ -0,0 +1,221 @@
+///////////////////////////////////////////////////////////////////////////////
+//
+//  Copyright 2018-2020 Airalab <research@aira.life>
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+///////////////////////////////////////////////////////////////////////////////
+//! Merkle Mountain Range over account datalog records.
+//!
+//! Nodes are numbered in post-order, leaves are appended to the right. Root of the range
+//! is the hash of its peaks, highest first.
+//!
+//! Only peaks are kept in runtime storage, every node is written into offchain indexing
+//! storage on append, so proofs are built off-chain from `O(log n)` nodes.
+
+use frame_support::{
+    codec::{Decode, Encode},
+    sp_runtime::{traits::Hash, RuntimeDebug},
+    sp_std::prelude::*,
+};
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+
+/// Inclusion proof of a datalog record.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct MerkleProof<H> {
+    /// Index of proven leaf.
+    pub leaf_index: u64,
+    /// Number of leaves in the range the proof was made for.
+    pub leaf_count: u64,
+    /// Sibling hashes from the leaf up to its peak.
+    pub items: Vec<H>,
+    /// All peaks of the range, highest first.
+    pub peaks: Vec<H>,
+}
+
+/// Hash of datalog record used as a leaf.
+pub fn leaf_hash<H: Hash, M: Encode, R: Encode>(moment: &M, record: &R) -> H::Output {
+    H::hash_of(&(moment, record))
+}
+
+/// Hash of two child nodes.
+pub fn merge<H: Hash>(left: &H::Output, right: &H::Output) -> H::Output {
+    H::hash_of(&(left, right))
+}
+
+/// Root of the range with given peaks.
+pub fn root<H: Hash>(peaks: &[H::Output]) -> H::Output {
+    H::hash_of(&peaks)
+}
+
+/// Position of leaf with given index.
+pub fn leaf_pos(index: u64) -> u64 {
+    2 * index - index.count_ones() as u64
+}
+
+/// Number of nodes in the range of `leaves` leaves.
+pub fn size(leaves: u64) -> u64 {
+    2 * leaves - leaves.count_ones() as u64
+}
+
+/// Number of merges done after appending a leaf with given index.
+pub fn merges(index: u64) -> u32 {
+    (!index).trailing_zeros()
+}
+
+/// Position of left sibling of the node at `pos` with `height`.
+pub fn left_sibling(pos: u64, height: u32) -> u64 {
+    pos - ((2 << height) - 1)
+}
+
+/// Append leaf with given index to the range with given peaks, highest first.
+///
+/// Returns appended nodes, the leaf first, their positions follow `leaf_pos(index)`.
+pub fn push<H: Hash>(peaks: &mut Vec<H::Output>, index: u64, leaf: H::Output) -> Vec<H::Output> {
+    let mut nodes = Vec::with_capacity(merges(index) as usize + 1);
+    nodes.push(leaf);
+    for _ in 0..merges(index) {
+        let left = peaks.pop().unwrap_or_default();
+        let hash = merge::<H>(&left, nodes.last().expect("nodes are not empty; qed"));
+        nodes.push(hash);
+    }
+    peaks.push(nodes.last().expect("nodes are not empty; qed").clone());
+    nodes
+}
+
+/// Peaks of the range of `leaves` leaves as `(position, height, first leaf)`, highest first.
+pub fn peaks(leaves: u64) -> Vec<(u64, u32, u64)> {
+    let mut peaks = Vec::new();
+    let mut offset = 0;
+    let mut first_leaf = 0;
+    for height in (0..64).rev() {
+        if leaves & (1 << height) != 0 {
+            offset += (2 << height) - 1;
+            peaks.push((offset - 1, height, first_leaf));
+            first_leaf += 1 << height;
+        }
+    }
+    peaks
+}
+
+/// Positions of the proof items for leaf and number of its peak.
+pub fn proof_positions(leaf_index: u64, leaves: u64) -> Option<(Vec<u64>, usize)> {
+    if leaf_index >= leaves {
+        return None;
+    }
+    let (peak, (_, height, first_leaf)) = peaks(leaves)
+        .into_iter()
+        .enumerate()
+        .find(|(_, peak)| leaf_index < peak.2 + (1 << peak.1))?;
+
+    let local = leaf_index - first_leaf;
+    let mut pos = leaf_pos(leaf_index);
+    let mut items = Vec::with_capacity(height as usize);
+    for h in 0..height {
+        if local & (1 << h) == 0 {
+            let sibling = pos + (2 << h) - 1;
+            items.push(sibling);
+            pos = sibling + 1;
+        } else {
+            items.push(left_sibling(pos, h));
+            pos += 1;
+        }
+    }
+    Some((items, peak))
+}
+
+/// Inclusion proof of `leaf_index`-th leaf into the range with given peaks.
+///
+/// Fetches only `O(log n)` proof items by their positions, returns `None` when the leaf
+/// is out of range or some node is missing.
+pub fn proof_from_nodes<H: Hash>(
+    leaf_index: u64,
+    leaf_count: u64,
+    peaks: Vec<H::Output>,
+    mut node: impl FnMut(u64) -> Option<H::Output>,
+) -> Option<MerkleProof<H::Output>> {
+    let (positions, _) = proof_positions(leaf_index, leaf_count)?;
+    let items = positions.into_iter().map(&mut node).collect::<Option<Vec<_>>>()?;
+    Some(MerkleProof { leaf_index, leaf_count, items, peaks })
+}
+
+/// Inclusion proof of `leaf_index`-th leaf into the range of given leaves.
+///
+/// Rebuilds the whole range, so is meant to be used off-chain.
+pub fn build_proof<H: Hash>(
+    leaves: &[H::Output],
+    leaf_index: u64,
+) -> Option<MerkleProof<H::Output>> {
+    let leaf_count = leaves.len() as u64;
+    let (positions, _) = proof_positions(leaf_index, leaf_count)?;
+
+    let mut nodes = Vec::with_capacity(size(leaf_count) as usize);
+    for (index, leaf) in leaves.iter().enumerate() {
+        nodes.push(leaf.clone());
+        for height in 0..merges(index as u64) {
+            let pos = nodes.len() as u64 - 1;
+            let hash = merge::<H>(
+                &nodes[left_sibling(pos, height) as usize],
+                &nodes[pos as usize],
+            );
+            nodes.push(hash);
+        }
+    }
+
+    Some(MerkleProof {
+        leaf_index,
+        leaf_count,
+        items: positions
+            .into_iter()
+            .map(|pos| nodes[pos as usize].clone())
+            .collect(),
+        peaks: peaks(leaf_count)
+            .into_iter()
+            .map(|(pos, _, _)| nodes[pos as usize].clone())
+            .collect(),
+    })
+}
+
+/// Check that `leaf` is included into range with given `root`.
+///
+/// Doesn't touch runtime storage, so could be used off-chain.
+pub fn verify_proof<H: Hash>(
+    root_hash: &H::Output,
+    leaf: &H::Output,
+    proof: &MerkleProof<H::Output>,
+) -> bool {
+    let (positions, peak) = match proof_positions(proof.leaf_index, proof.leaf_count) {
+        Some(v) => v,
+        None => return false,
+    };
+    if positions.len() != proof.items.len() || peaks(proof.leaf_count).len() != proof.peaks.len()
+    {
+        return false;
+    }
+
+    let (_, _, first_leaf) = peaks(proof.leaf_count)[peak];
+    let local = proof.leaf_index - first_leaf;
+    let mut hash = leaf.clone();
+    for (h, sibling) in proof.items.iter().enumerate() {
+        hash = if local & (1 << h) == 0 {
+            merge::<H>(&hash, sibling)
+        } else {
+            merge::<H>(sibling, &hash)
+        };
+    }
+
+    proof.peaks[peak] == hash && root::<H>(&proof.peaks) == *root_hash
+}
+
@@ -0,0 +1,132 @@
+///////////////////////////////////////////////////////////////////////////////
+//
+//  Copyright 2018-2020 Airalab <research@aira.life>
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+///////////////////////////////////////////////////////////////////////////////
+//! Typed datalog records.
+use frame_support::{
+    codec::{Decode, Encode},
+    sp_runtime::RuntimeDebug,
+    sp_std::prelude::*,
+};
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+
+/// Format of datalog record content.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum Format {
+    /// Raw bytes, e.g. sensor reading.
+    Raw,
+    /// Binary IPFS content identifier (CIDv0 multihash or CIDv1).
+    Cid,
+    /// UTF-8 text.
+    Text,
+    /// SCALE encoded blob.
+    Scale,
+}
+
+impl Default for Format {
+    fn default() -> Self {
+        Format::Raw
+    }
+}
+
+/// Datalog record content description.
+pub trait Content {
+    /// Format of record content.
+    fn format(&self) -> Format;
+    /// Check that record content matches its format.
+    fn is_valid(&self) -> bool;
+}
+
+impl Content for Vec<u8> {
+    fn format(&self) -> Format {
+        Format::Raw
+    }
+
+    fn is_valid(&self) -> bool {
+        true
+    }
+}
+
+/// Datalog record tagged with content format.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Default, RuntimeDebug)]
+pub struct TypedRecord {
+    /// Content format.
+    pub format: Format,
+    /// Record content.
+    pub data: Vec<u8>,
+}
+
+impl TypedRecord {
+    pub fn new(format: Format, data: Vec<u8>) -> Self {
+        Self { format, data }
+    }
+}
+
+impl From<Vec<u8>> for TypedRecord {
+    fn from(data: Vec<u8>) -> Self {
+        Self::new(Format::Raw, data)
+    }
+}
+
+impl Content for TypedRecord {
+    fn format(&self) -> Format {
+        self.format
+    }
+
+    fn is_valid(&self) -> bool {
+        match self.format {
+            Format::Raw | Format::Scale => true,
+            Format::Cid => is_valid_cid(&self.data),
+            Format::Text => core::str::from_utf8(&self.data).is_ok(),
+        }
+    }
+}
+
+/// Check binary encoding of IPFS content identifier.
+///
+/// CIDv0 is a bare sha2-256 multihash, CIDv1 is a version byte followed
+/// by content codec and multihash.
+pub fn is_valid_cid(data: &[u8]) -> bool {
+    match data {
+        [0x12, 0x20, ..] => is_valid_multihash(data),
+        [0x01, cid @ ..] => {
+            read_varint(cid).map_or(false, |(_codec, multihash)| is_valid_multihash(multihash))
+        }
+        _ => false,
+    }
+}
+
+/// Multihash is a hash function code and digest length followed by digest.
+fn is_valid_multihash(data: &[u8]) -> bool {
+    read_varint(data)
+        .and_then(|(_code, rest)| read_varint(rest))
+        .map_or(false, |(length, digest)| digest.len() as u64 == length)
+}
+
+/// Read unsigned varint, it is at most 9 bytes long by multiformats spec.
+fn read_varint(data: &[u8]) -> Option<(u64, &[u8])> {
+    let mut value = 0u64;
+    for (i, byte) in data.iter().enumerate().take(9) {
+        value |= u64::from(byte & 0x7f) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Some((value, &data[i + 1..]));
+        }
+    }
+    None
+}